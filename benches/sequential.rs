@@ -0,0 +1,93 @@
+//! Measures the library on the boards of less than 16 columns, where it takes the sequential paths,
+//! and on the larger boards, where it takes the parallel ones, so that the threshold can be checked
+//! by the jump of the timings between the sizes 12 and 16.
+//!
+//! Run with `cargo bench --bench sequential`
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use queen_placement::{
+    board::{Board, Piece},
+    chromosome::Chromosome,
+    selection::{Probability, SelectionCtx, SelectionParams, SelectionStrategy},
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+const SIZES: [u16; 4] = [8, 12, 16, 32];
+
+const POPULATION_SIZE: usize = 100;
+
+fn beats_count(c: &mut Criterion) {
+    let mut group = c.benchmark_group("beats_count");
+    let mut rng = StdRng::seed_from_u64(42);
+    for size in SIZES {
+        let chromosome = Chromosome::new(size, &mut rng).with_piece(Piece::Knight);
+        group.bench_with_input(BenchmarkId::from_parameter(size), &chromosome, |b, ch| {
+            b.iter(|| Board::from(black_box(ch)).beats_count())
+        });
+    }
+    group.finish();
+}
+
+fn hamming_distance(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hamming_distance");
+    let mut rng = StdRng::seed_from_u64(42);
+    for size in SIZES {
+        let pair = (
+            Chromosome::new(size, &mut rng),
+            Chromosome::new(size, &mut rng),
+        );
+        group.bench_with_input(BenchmarkId::from_parameter(size), &pair, |b, (ch1, ch2)| {
+            b.iter(|| black_box(ch1).hamming_distance(black_box(ch2)))
+        });
+    }
+    group.finish();
+}
+
+fn create_generation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("create_generation");
+    for size in SIZES {
+        group.bench_function(BenchmarkId::from_parameter(size), |b| {
+            let mut rng = StdRng::seed_from_u64(42);
+            b.iter(|| Chromosome::create_generation(size, POPULATION_SIZE, &mut rng))
+        });
+    }
+    group.finish();
+}
+
+fn next_generation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("next_generation");
+    let mutation_probability = Probability::new(0.1).unwrap();
+    for strategy in [SelectionStrategy::Tournament, SelectionStrategy::Crowding] {
+        for size in SIZES {
+            group.bench_function(BenchmarkId::new(strategy.to_string(), size), |b| {
+                let mut rng = StdRng::seed_from_u64(42);
+                let generation = Chromosome::create_generation(size, POPULATION_SIZE, &mut rng);
+                let mut selection = strategy.selection();
+                b.iter_batched(
+                    || generation.clone(),
+                    |generation| {
+                        selection.next_generation(
+                            generation,
+                            &mut SelectionCtx {
+                                mutation_probability,
+                                params: SelectionParams::default(),
+                                rng: &mut rng,
+                            },
+                        )
+                    },
+                    BatchSize::SmallInput,
+                )
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    beats_count,
+    hamming_distance,
+    create_generation,
+    next_generation
+);
+criterion_main!(benches);
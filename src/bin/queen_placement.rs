@@ -1,79 +1,242 @@
 use std::{
-    collections::HashSet,
-    io::{stdout, Write},
+    env,
+    fs::File,
+    io::{stdout, IsTerminal, LineWriter, Write},
+    path::Path,
+    process,
 };
 
-use clap::Parser;
+use clap::{error::ErrorKind, CommandFactory, Parser};
 use crossterm::{
     cursor, queue,
     style::Print,
     terminal::{Clear, ClearType},
 };
 use queen_placement::{
-    board::Board,
-    chromosome::Chromosome,
-    config::Config,
-    selection::{self, Generation},
+    board::{self, Board, Glyph},
+    config::{Config, OutputFormat},
+    solver::{self, Progress, Solver, SolverState, Termination},
+    stats::{self, GenerationStats},
 };
-use rayon::prelude::{
-    FromParallelIterator, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator,
+#[cfg(feature = "serde")]
+use queen_placement::{
+    chromosome::Chromosome,
+    snapshot::{BoardSnapshot, Checkpoint},
 };
 
 fn main() {
-    let Config {
-        board_size,
-        generation_size,
-        mutation_probability,
-        selection_strategy,
-    } = Config::parse();
-
-    if generation_size.get() < 2 {
-        println!("Generation size os too small");
-        return;
+    let mut config = Config::parse();
+    if let Err(e) = config.validate() {
+        Config::command()
+            .error(ErrorKind::ArgumentConflict, e)
+            .exit();
     }
 
-    let mut generation = Chromosome::create_generation(board_size.get(), generation_size.get());
-    let mut generation_count: u32 = 1;
-    print_max_fitness(&generation, &generation_count);
+    let solver_state = load_checkpoint(&mut config);
 
-    while !generation.par_iter().any(is_fitting) {
-        generation =
-            selection::new_generation(selection_strategy, generation, mutation_probability);
-        generation_count += 1;
-        print_max_fitness(&generation, &generation_count);
+    if config.glyph == Glyph::Unicode && !supports_unicode() {
+        config.glyph = Glyph::Blocks;
+    }
+    if !stdout().is_terminal() {
+        config.no_color = true;
     }
 
-    let mut stdout = stdout();
-    queue!(
-        stdout,
-        Clear(ClearType::All),
-        Clear(ClearType::Purge),
-        crossterm::cursor::MoveTo(0, 0)
-    )
-    .unwrap();
-    stdout.flush().unwrap();
-    let fitting_individuals: HashSet<Chromosome> =
-        HashSet::from_par_iter(generation.into_par_iter().filter(is_fitting));
-    for individual in fitting_individuals {
+    let (width, height) = (config.board_width(), config.board_height());
+    if !board::is_solvable_on(config.piece, config.pieces(), height) {
+        println!(
+            "No solution exists for the {width}x{height} board and {} {} pieces",
+            config.pieces(),
+            config.piece
+        );
+        process::exit(1);
+    }
+
+    let thread_pool = config.thread_pool().unwrap_or_else(|e| {
+        Config::command()
+            .error(
+                ErrorKind::Io,
+                format!("Failed to build the thread pool: {e:#}"),
+            )
+            .exit()
+    });
+
+    #[cfg(feature = "serde")]
+    let mut stats_json = config
+        .stats_json
+        .as_deref()
+        .map(|path| open_stats_file(path, None));
+    let mut stats_csv = config
+        .stats_csv
+        .as_deref()
+        .map(|path| open_stats_file(path, Some(stats::CSV_HEADER)));
+
+    let search = || {
+        let mut solver = Solver::new(&config);
+        if let Some(state) = solver_state {
+            solver = solver.resume_from(state);
+        }
+        solver.solve_with_progress(|progress| {
+            if config.shows_progress(progress.generation_count) {
+                print_max_fitness(progress, config.glyph);
+            }
+            #[cfg(feature = "serde")]
+            let stats_wanted = config.verbose || stats_json.is_some() || stats_csv.is_some();
+            #[cfg(not(feature = "serde"))]
+            let stats_wanted = config.verbose || stats_csv.is_some();
+            if stats_wanted {
+                let stats = progress.stats();
+                if config.verbose {
+                    println!("{stats}");
+                }
+                #[cfg(feature = "serde")]
+                if let Some(writer) = &mut stats_json {
+                    write_stats_json(writer, &stats);
+                }
+                if let Some(writer) = &mut stats_csv {
+                    write_stats_csv(writer, &stats);
+                }
+            }
+            #[cfg(feature = "serde")]
+            save_checkpoint(&config, progress);
+        })
+    };
+    let outcome = match &thread_pool {
+        Some(pool) => pool.install(search),
+        None => search(),
+    }
+    .unwrap_or_else(|e| {
+        Config::command()
+            .error(ErrorKind::ArgumentConflict, e)
+            .exit()
+    });
+    let generation_count = outcome.generation_count;
+
+    #[cfg(feature = "svg")]
+    if let Some(path) = &config.export_svg {
+        let exported = outcome.solutions.iter().next().unwrap_or(&outcome.best);
+        if let Err(e) = export_svg(&Board::from(exported), path) {
+            eprintln!("Failed to export the board: {e}");
+            process::exit(1);
+        }
+    }
+
+    match outcome.termination {
+        Termination::Solved => {}
+        _ if !outcome.solutions.is_empty() => {}
+        Termination::ExhaustedGenerations => {
+            let best = Board::from(&outcome.best);
+            println!(
+                "No solution was found in {generation_count} generations. The best board ({} beaten):\n{}",
+                best.beats_count(),
+                render(&best, &config)
+            );
+            process::exit(1);
+        }
+        Termination::TimeLimit => {
+            let best = Board::from(&outcome.best);
+            println!(
+                "No solution was found in time ({generation_count} generations). The best board (fitness {}):\n{:?}\n{}",
+                best.fitness(),
+                outcome.best.genes(),
+                render(&best, &config)
+            );
+            process::exit(1);
+        }
+        Termination::Stagnation => {
+            let best = Board::from(&outcome.best);
+            println!(
+                "The search has stagnated after {generation_count} generations. The best board ({} beaten):\n{}",
+                best.beats_count(),
+                render(&best, &config)
+            );
+            process::exit(1);
+        }
+    }
+
+    if config.is_animated() {
+        let mut stdout = stdout();
+        queue!(
+            stdout,
+            Clear(ClearType::All),
+            Clear(ClearType::Purge),
+            crossterm::cursor::MoveTo(0, 0)
+        )
+        .unwrap();
+        stdout.flush().unwrap();
+    }
+    let solutions_found = outcome.solutions.len();
+    for individual in outcome.solutions {
         let board = Board::from(&individual);
-        println!("{board}\n({generation_count})");
+        println!("{}\n({generation_count})", render(&board, &config));
+    }
+    match config.solutions_target() {
+        Some(target) if outcome.termination != Termination::Solved => {
+            println!(
+                "Only {solutions_found} of {target} solutions were found in {generation_count} generations"
+            );
+            process::exit(1);
+        }
+        _ if config.collect_all => {
+            println!(
+                "Collected {solutions_found} unique solutions in {generation_count} generations"
+            )
+        }
+        _ => {}
     }
 }
 
-fn is_fitting(ch: &Chromosome) -> bool {
-    Board::from(ch).fitness() == 1.0
+fn render(board: &Board, config: &Config) -> String {
+    match config.output_format {
+        OutputFormat::Pretty if !config.no_color => board.with_glyph(config.glyph).to_string(),
+        OutputFormat::Pretty | OutputFormat::Ascii => board.render_plain(),
+        OutputFormat::Fen => board.to_fen(),
+        #[cfg(feature = "serde")]
+        OutputFormat::Json => {
+            let snapshot = BoardSnapshot::from(<&Chromosome>::from(board.clone()));
+            serde_json::to_string(&snapshot).expect("The snapshot is serializable")
+        }
+        // rejected by the config validation
+        #[cfg(not(feature = "serde"))]
+        OutputFormat::Json => unreachable!("The JSON output requires the `serde` feature"),
+    }
 }
 
-fn print_max_fitness(gen: &Generation, generation_count: &u32) {
-    let (b, ch) = gen
-        .par_iter()
-        .map(|chromosome| {
-            let board = Board::from(chromosome);
-            let fitness = board.fitness();
-            (board, fitness)
+/// Approximate side of the exported SVG image in pixels
+#[cfg(feature = "svg")]
+const SVG_SIDE: u32 = 800;
+
+/// Writes the board as SVG, scaling the cells so that the image stays about [`SVG_SIDE`] pixels wide
+#[cfg(feature = "svg")]
+fn export_svg(board: &Board, path: &Path) -> std::io::Result<()> {
+    use queen_placement::chromosome::Chromosome;
+
+    let chromosome: &Chromosome = board.clone().into();
+    let cells = chromosome.width().max(chromosome.height()).max(1) as u32;
+    std::fs::write(path, board.to_svg((SVG_SIDE / cells).max(1)))
+}
+
+/// Guesses the Unicode support of the terminal from the locale
+fn supports_unicode() -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .find_map(|name| env::var(name).ok().filter(|value| !value.is_empty()))
+        .is_some_and(|locale| {
+            let locale = locale.to_ascii_uppercase();
+            locale.contains("UTF-8") || locale.contains("UTF8")
         })
-        .max_by(|(_, f1), (_, f2)| f32::partial_cmp(f1, f2).unwrap())
-        .unwrap();
+}
+
+fn print_max_fitness(
+    Progress {
+        generation,
+        generation_count,
+        evaluations,
+        ..
+    }: Progress<'_>,
+    glyph: Glyph,
+) {
+    let b = Board::from(solver::best_individual(generation));
+    let ch = b.fitness();
 
     let mut stdout = stdout();
     queue!(
@@ -81,8 +244,110 @@ fn print_max_fitness(gen: &Generation, generation_count: &u32) {
         Clear(ClearType::All),
         Clear(ClearType::Purge),
         cursor::MoveTo(0, 0),
-        Print(format!("{ch} ({generation_count})\n{b}"))
+        Print(format!(
+            "{ch} ({generation_count}; {evaluations} evaluations)\n{}",
+            b.with_glyph(glyph)
+        ))
     )
     .unwrap();
     stdout.flush().unwrap();
 }
+
+/// Replaces the config with the one from the `--resume` checkpoint, keeping the checkpoint arguments
+#[cfg(feature = "serde")]
+fn load_checkpoint(config: &mut Config) -> Option<SolverState> {
+    let path = config.resume.as_ref()?;
+    let Checkpoint {
+        config: saved,
+        state,
+    } = match Checkpoint::load(path) {
+        Ok(checkpoint) => checkpoint,
+        Err(e) => Config::command()
+            .error(ErrorKind::Io, format!("{e:#}"))
+            .exit(),
+    };
+    *config = Config {
+        checkpoint: config.checkpoint.take(),
+        checkpoint_interval: config.checkpoint_interval,
+        no_color: config.no_color,
+        quiet: config.quiet,
+        verbose: config.verbose,
+        refresh_every: config.refresh_every,
+        glyph: config.glyph,
+        output_format: config.output_format,
+        #[cfg(feature = "svg")]
+        export_svg: config.export_svg.take(),
+        stats_json: config.stats_json.take(),
+        stats_csv: config.stats_csv.take(),
+        resume: config.resume.take(),
+        ..saved
+    };
+    Some(state)
+}
+
+#[cfg(not(feature = "serde"))]
+fn load_checkpoint(_config: &mut Config) -> Option<SolverState> {
+    None
+}
+
+#[cfg(feature = "serde")]
+fn save_checkpoint(config: &Config, progress: Progress<'_>) {
+    let Some(path) = &config.checkpoint else {
+        return;
+    };
+    if !progress
+        .generation_count
+        .is_multiple_of(config.checkpoint_interval.get())
+    {
+        return;
+    }
+    if let Err(e) = Checkpoint::new(config, progress).save(path) {
+        eprintln!("Failed to save the checkpoint: {e:#}");
+        process::exit(1);
+    }
+}
+
+/// Opens the file to append the statistics to, starting the new or empty file with the `header` line
+fn open_stats_file(path: &Path, header: Option<&str>) -> LineWriter<File> {
+    let opened = File::options()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|file| {
+            let is_empty = file.metadata()?.len() == 0;
+            let mut writer = LineWriter::new(file);
+            if let Some(header) = header.filter(|_| is_empty) {
+                writeln!(writer, "{header}")?;
+            }
+            Ok(writer)
+        });
+    opened.unwrap_or_else(|e| {
+        Config::command()
+            .error(
+                ErrorKind::Io,
+                format!("Failed to open the statistics file: {e}"),
+            )
+            .exit()
+    })
+}
+
+/// Appends the statistics of the generation as one JSON line
+#[cfg(feature = "serde")]
+fn write_stats_json(writer: &mut impl Write, stats: &GenerationStats) {
+    let written = serde_json::to_writer(&mut *writer, stats)
+        .map_err(std::io::Error::from)
+        .and_then(|()| writeln!(writer));
+    if let Err(e) = written {
+        eprintln!("Failed to write the statistics: {e}");
+        process::exit(1);
+    }
+}
+
+/// Appends the statistics of the generation as one CSV row.
+/// Every row is flushed, so the file stays usable, if the search is interrupted
+fn write_stats_csv(writer: &mut impl Write, stats: &GenerationStats) {
+    if let Err(e) = writeln!(writer, "{}", stats.to_csv_row()) {
+        eprintln!("Failed to write the statistics: {e}");
+        process::exit(1);
+    }
+}
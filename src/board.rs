@@ -1,17 +1,213 @@
-use std::fmt;
+use std::{collections::HashSet, fmt, path::Path, str::FromStr};
+
+use anyhow::{bail, Context};
 
 use crossterm::style::{Color, Stylize};
-use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
 
-use crate::chromosome::Chromosome;
+use crate::chromosome::{check_rows, Chromosome, Gene, SEQUENTIAL_LEN};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Board<'ch>(&'ch Chromosome);
 
+/// Marker of a queen in the colored rendering of a [`Board`]
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Glyph {
+    /// `♛`, that requires a terminal with the Unicode support
+    Unicode,
+    /// `##`
+    #[default]
+    Blocks,
+    /// `Q`
+    Ascii,
+}
+
+/// Chess piece, that is placed in every column of the board
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Piece {
+    #[default]
+    Queen,
+    Rook,
+    Bishop,
+    Knight,
+    /// Moves both as a queen and as a knight
+    Amazon,
+}
+
+impl Piece {
+    /// Letter of the piece in the chess notation. The amazon has none, so it is `A`
+    fn letter(self) -> char {
+        match self {
+            Piece::Queen => 'Q',
+            Piece::Rook => 'R',
+            Piece::Bishop => 'B',
+            Piece::Knight => 'N',
+            Piece::Amazon => 'A',
+        }
+    }
+
+    /// Whether the pieces in the different columns, `dx` columns and `dy` rows apart, attack each other
+    fn attacks(self, dx: usize, dy: usize) -> bool {
+        let row = dy == 0;
+        let diagonal = dx == dy;
+        let knight = matches!((dx, dy), (1, 2) | (2, 1));
+        match self {
+            Piece::Queen => row || diagonal,
+            Piece::Rook => row,
+            Piece::Bishop => diagonal,
+            Piece::Knight => knight,
+            Piece::Amazon => row || diagonal || knight,
+        }
+    }
+}
+
+/// Cells of the board, that no piece may occupy, as the pairs of the column and the row
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockedCells(HashSet<(Gene, Gene)>);
+
+impl BlockedCells {
+    /// Reads the cells from a file with one `x y` or `x,y` pair per line.
+    /// The empty lines and the ones, starting with `#`, are skipped
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read the blocked cells from {}", path.display()))?;
+        text.parse()
+    }
+
+    #[inline]
+    pub fn contains(&self, x: Gene, y: Gene) -> bool {
+        self.0.contains(&(x, y))
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Checks, that every cell is on the board of `width` columns and `height` rows
+    pub fn check_bounds(&self, width: u16, height: u16) -> anyhow::Result<()> {
+        match self.0.iter().find(|(x, y)| *x >= width || *y >= height) {
+            Some((x, y)) => {
+                bail!("The blocked cell ({x}, {y}) is outside of the {width}x{height} board")
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl FromIterator<(Gene, Gene)> for BlockedCells {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = (Gene, Gene)>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl FromStr for BlockedCells {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(number, line)| {
+                let coordinates: Vec<&str> = line
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|part| !part.is_empty())
+                    .collect();
+                let [x, y] = coordinates[..] else {
+                    bail!("Expected two coordinates at line {number}, found {line:?}");
+                };
+                let parse = |coordinate: &str| {
+                    coordinate.parse::<Gene>().with_context(|| {
+                        format!("Invalid coordinate {coordinate:?} at line {number}")
+                    })
+                };
+                Ok((parse(x)?, parse(y)?))
+            })
+            .collect()
+    }
+}
+
+/// Pieces, pinned to the rows of their columns, as the pairs of the column and the row.
+/// No two of them share a column or a row
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FixedPieces(Vec<(Gene, Gene)>);
+
+impl FixedPieces {
+    /// Checks, that the `pieces` are in the distinct columns and rows
+    pub fn new(pieces: Vec<(Gene, Gene)>) -> anyhow::Result<Self> {
+        for (i, (x1, y1)) in pieces.iter().enumerate() {
+            for (x2, y2) in &pieces[..i] {
+                if x1 == x2 {
+                    bail!("Column {x1} is fixed twice");
+                }
+                if y1 == y2 {
+                    bail!("Row {y1} is fixed in both columns {x2} and {x1}");
+                }
+            }
+        }
+        Ok(Self(pieces))
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (Gene, Gene)> + '_ {
+        self.0.iter().copied()
+    }
+
+    /// Checks, that every piece is on the board of `width` columns and `height` rows
+    pub fn check_bounds(&self, width: u16, height: u16) -> anyhow::Result<()> {
+        match self.0.iter().find(|(x, y)| *x >= width || *y >= height) {
+            Some((x, y)) => {
+                bail!("The fixed piece {x}:{y} is outside of the {width}x{height} board")
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl FromStr for FixedPieces {
+    type Err = anyhow::Error;
+
+    /// Parses the comma-separated `column:row` pairs
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let pieces = s
+            .split(',')
+            .map(|piece| {
+                let (x, y) = piece
+                    .trim()
+                    .split_once(':')
+                    .with_context(|| format!("Expected `column:row`, found {piece:?}"))?;
+                Ok((x.trim().parse()?, y.trim().parse()?))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Self::new(pieces)
+    }
+}
+
+impl Glyph {
+    /// Text of one cell with a queen
+    fn cell(self) -> &'static str {
+        match self {
+            Glyph::Unicode => "\u{265B} ",
+            Glyph::Blocks => "##",
+            Glyph::Ascii => "Q ",
+        }
+    }
+}
+
 impl<'ch> PartialOrd for Board<'ch> {
     #[inline]
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.fitness().partial_cmp(&other.fitness())
+        Some(self.cmp(other))
     }
 }
 
@@ -23,18 +219,112 @@ impl<'ch> Ord for Board<'ch> {
 }
 
 impl<'ch> Board<'ch> {
-    /// Amount of chess pieces that have been beaten
+    /// Amount of chess pieces that have been beaten by the piece of the chromosome.
+    ///
+    /// The genes do not have to be a permutation: the pieces, that share a row, are counted as well
+    #[inline]
     pub fn beats_count(&self) -> u16 {
+        self.beats_count_for(self.0.piece)
+    }
+
+    /// Amount of chess pieces that have been beaten, if they all are `piece`
+    pub fn beats_count_for(&self, piece: Piece) -> u16 {
+        match piece {
+            Piece::Queen if has_distinct_rows(&self.0.genes) => self.diagonal_beats_count(),
+            _ => self.pairwise_beats_count(piece),
+        }
+    }
+
+    /// Columns of the pieces, that are beaten, in the ascending order
+    pub fn conflicting_columns(&self) -> Vec<usize> {
+        match self.0.piece {
+            Piece::Queen if has_distinct_rows(&self.0.genes) => self.diagonal_conflicts().collect(),
+            piece if self.0.genes.len() < SEQUENTIAL_LEN => (0..self.0.genes.len())
+                .filter(|x| self.is_beaten(piece, *x))
+                .collect(),
+            piece => (0..self.0.genes.len())
+                .into_par_iter()
+                .filter(|x| self.is_beaten(piece, *x))
+                .collect(),
+        }
+    }
+
+    /// Checks every pair of the pieces, so it works for any genes
+    fn pairwise_beats_count(&self, piece: Piece) -> u16 {
+        let len = self.0.genes.len();
+        if len < SEQUENTIAL_LEN {
+            (0..len).filter(|x| self.is_beaten(piece, *x)).count() as u16
+        } else {
+            (0..len)
+                .into_par_iter()
+                .filter(|x| self.is_beaten(piece, *x))
+                .count() as u16
+        }
+    }
+
+    /// Whether the piece in the column `x1` is attacked by any other one
+    fn is_beaten(&self, piece: Piece, x1: usize) -> bool {
+        let y1 = self.0.genes[x1];
         self.0
             .genes
-            .par_iter()
+            .iter()
             .enumerate()
-            .filter(|(x1, y1)| {
-                self.0.genes.par_iter().enumerate().any(|(x2, y2)| {
-                    x1 != &x2 && x1.abs_diff(x2) == y1.abs_diff(*y2) as usize // simplified version without horizontal intersection testing
-                })
+            .any(|(x2, y2)| x1 != x2 && piece.attacks(x1.abs_diff(x2), y1.abs_diff(*y2) as usize))
+    }
+
+    #[inline]
+    fn diagonal_beats_count(&self) -> u16 {
+        self.diagonal_conflicts().count() as u16
+    }
+
+    /// Counts the pieces on every diagonal in O(width + height).
+    ///
+    /// Only valid when the genes are distinct, so that no pieces share a row
+    fn diagonal_conflicts(&self) -> impl Iterator<Item = usize> + '_ {
+        let genes = &self.0.genes;
+        let width = genes.len();
+        let height = genes.iter().max().map_or(0, |y| *y as usize + 1);
+
+        // "/" diagonals are indexed by `x + y` and "\" diagonals by `x + (height - 1 - y)`
+        let mut ascending = vec![0u16; width + height];
+        let mut descending = vec![0u16; width + height];
+        for (x, y) in genes.iter().enumerate() {
+            ascending[x + *y as usize] += 1;
+            descending[x + height - 1 - *y as usize] += 1;
+        }
+
+        genes
+            .iter()
+            .enumerate()
+            .filter(move |(x, y)| {
+                ascending[x + **y as usize] > 1 || descending[x + height - 1 - **y as usize] > 1
             })
-            .count() as u16
+            .map(|(x, _)| x)
+    }
+
+    /// Whether there is a piece in the column `x` and the row `y`. The columns past the genes are empty
+    #[inline]
+    fn has_piece(&self, x: usize, y: Gene) -> bool {
+        self.0.genes.get(x) == Some(&y)
+    }
+
+    /// Amount of the pieces, that stand on the blocked cells of the chromosome
+    pub fn blocked_count(&self) -> u16 {
+        self.0.blocked().map_or(0, |blocked| {
+            self.0
+                .genes
+                .iter()
+                .enumerate()
+                .filter(|(x, y)| blocked.contains(*x as Gene, **y))
+                .count() as u16
+        })
+    }
+
+    /// Whether no piece is beaten nor stands on a blocked cell.
+    /// Unlike the comparison of the [`Board::fitness`] to 1, it does not depend on the fitness formula
+    #[inline]
+    pub fn is_solution(&self) -> bool {
+        self.beats_count() == 0 && self.blocked_count() == 0
     }
 
     /// Returns the fitness of this [`Board`].
@@ -42,10 +332,242 @@ impl<'ch> Board<'ch> {
     /// The goal is to maximize the function to be 1
     #[inline]
     pub fn fitness(&self) -> f32 {
-        1.0 / (self.beats_count() as f32 + 1.0)
+        self.0.fitness()
+    }
+
+    /// Colored rendering of the board, that marks the queens with the `glyph`
+    #[inline]
+    pub fn with_glyph(&self, glyph: Glyph) -> impl fmt::Display + '_ {
+        WithGlyph(self, glyph)
+    }
+
+    /// Renders the board as ASCII text without the escape sequences:
+    /// `Q` for a queen, `x` for an empty blocked cell and `.` for an empty cell
+    pub fn render_plain(&self) -> String {
+        let (width, height) = (self.0.width() as usize, self.0.height());
+        let mut rendered = String::with_capacity((width + 1) * height as usize);
+        for y in 0..height {
+            rendered.extend((0..width).map(|x| {
+                if self.has_piece(x, y) {
+                    'Q'
+                } else if self.0.is_blocked(x as Gene, y) {
+                    'x'
+                } else {
+                    '.'
+                }
+            }));
+            rendered.push('\n');
+        }
+        rendered
+    }
+
+    /// Placement of the pieces in the FEN notation: the rows from the top one, separated by `/`,
+    /// where the runs of the empty cells are replaced by their lengths
+    pub fn to_fen(&self) -> String {
+        let letter = self.0.piece.letter();
+        let rows: Vec<String> = (0..self.0.height())
+            .map(|y| {
+                let mut row = String::new();
+                let mut empty = 0;
+                for x in 0..self.0.width() as usize {
+                    if self.has_piece(x, y) {
+                        if empty > 0 {
+                            row += &empty.to_string();
+                            empty = 0;
+                        }
+                        row.push(letter);
+                    } else {
+                        empty += 1;
+                    }
+                }
+                if empty > 0 {
+                    row += &empty.to_string();
+                }
+                row
+            })
+            .collect();
+        rows.join("/")
+    }
+
+    /// Renders the board as a standalone SVG image with the cells of `cell_size` pixels.
+    ///
+    /// The cells are shrunk, if a side of the image would not fit into `u32` pixels
+    #[cfg(feature = "svg")]
+    pub fn to_svg(&self, cell_size: u32) -> String {
+        use std::fmt::Write;
+
+        let (width, height) = (self.0.width() as u32, self.0.height() as u32);
+        let cell_size = cell_size.min(u32::MAX / width.max(height).max(1));
+        let (image_width, image_height) = (width * cell_size, height * cell_size);
+        let radius = cell_size as f32 * 0.35;
+
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{image_width}" height="{image_height}" viewBox="0 0 {image_width} {image_height}">"#
+        );
+        svg.push('\n');
+        for y in 0..height {
+            for x in 0..width {
+                let fill = if is_dark_cell(x as usize, y as usize) {
+                    "#A9A9A9"
+                } else {
+                    "#FFFFFF"
+                };
+                writeln!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="{cell_size}" height="{cell_size}" fill="{fill}"/>"#,
+                    x * cell_size,
+                    y * cell_size,
+                )
+                .unwrap();
+            }
+        }
+        for (x, y) in self.0.genes.iter().enumerate() {
+            let center = |i: u32| i as f32 * cell_size as f32 + cell_size as f32 / 2.0;
+            writeln!(
+                svg,
+                r##"<circle cx="{}" cy="{}" r="{radius}" fill="#8B0000"/>"##,
+                center(x as u32),
+                center(*y as u32),
+            )
+            .unwrap();
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Rasterizes the board with the cells of `cell_px` pixels into a PNG file.
+    ///
+    /// Fails, if a side of the image would exceed [`MAX_IMAGE_SIDE`] pixels
+    #[cfg(feature = "image")]
+    pub fn save_png(&self, path: &std::path::Path, cell_px: u32) -> anyhow::Result<()> {
+        use image::{Rgb, RgbImage};
+
+        let (width, height) = (self.0.width() as u32, self.0.height() as u32);
+        let side = |cells: u32| {
+            cells
+                .checked_mul(cell_px)
+                .filter(|side| *side <= MAX_IMAGE_SIDE)
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "The image of {width}x{height} cells of {cell_px} pixels exceeds {MAX_IMAGE_SIDE} pixels"
+                    )
+                })
+        };
+        let (image_width, image_height) = (side(width)?, side(height)?);
+        anyhow::ensure!(
+            image_width > 0 && image_height > 0,
+            "The image must not be empty"
+        );
+
+        let radius = cell_px as f32 * 0.35;
+        let image = RgbImage::from_fn(image_width, image_height, |px, py| {
+            let (x, y) = (px / cell_px, py / cell_px);
+            let (dx, dy) = (
+                (px % cell_px) as f32 + 0.5 - cell_px as f32 / 2.0,
+                (py % cell_px) as f32 + 0.5 - cell_px as f32 / 2.0,
+            );
+            if self.has_piece(x as usize, y as Gene) && dx * dx + dy * dy <= radius * radius {
+                Rgb([0x8B, 0x00, 0x00])
+            } else if is_dark_cell(x as usize, y as usize) {
+                Rgb([0xA9, 0xA9, 0xA9])
+            } else {
+                Rgb([0xFF, 0xFF, 0xFF])
+            }
+        });
+        image.save(path)?;
+        Ok(())
+    }
+
+    #[inline]
+    pub(crate) fn uncached_fitness(&self) -> f32 {
+        // a piece on a blocked cell is penalized as if it was beaten
+        1.0 / ((self.beats_count() + self.blocked_count()) as f32 + 1.0)
+    }
+}
+
+/// Maximal width and height of the image, produced by [`Board::save_png`]
+#[cfg(feature = "image")]
+pub const MAX_IMAGE_SIDE: u32 = 16384;
+
+/// Whether no two genes are the same, so that no pieces share a row
+fn has_distinct_rows(genes: &[Gene]) -> bool {
+    let rows = genes.iter().max().map_or(0, |y| *y as usize + 1);
+    let mut seen = vec![false; rows];
+    genes
+        .iter()
+        .all(|gene| !std::mem::replace(&mut seen[*gene as usize], true))
+}
+
+/// Whether it is possible to place `n` non-attacking queens on the `n`x`n` board.
+///
+/// It is impossible only for the boards of size 2 and 3
+#[inline]
+pub fn is_solvable(n: u16) -> bool {
+    is_solvable_for(Piece::Queen, n)
+}
+
+/// Whether it is possible to place `n` non-attacking `piece`s on the `n`x`n` board, one in every column.
+///
+/// Amazons can be placed only on the boards of size 1 and from 10 on
+pub fn is_solvable_for(piece: Piece, n: u16) -> bool {
+    match piece {
+        Piece::Queen | Piece::Bishop => !matches!(n, 2 | 3),
+        // the main diagonal is a solution
+        Piece::Rook | Piece::Knight => true,
+        Piece::Amazon => n == 1 || n >= 10,
+    }
+}
+
+/// Whether it is possible to place `width` non-attacking `piece`s on the board of `width` columns and `height` rows,
+/// one in every column.
+///
+/// The pieces, that attack along the rows, need the distinct rows, and the square solutions fit the taller boards.
+/// The amazons on the taller boards, as well as the bishops and knights on the wider ones,
+/// are only assumed to be solvable, so their search may need a limit
+pub fn is_solvable_on(piece: Piece, width: u16, height: u16) -> bool {
+    match piece {
+        _ if width == height => is_solvable_for(piece, width),
+        Piece::Queen | Piece::Rook | Piece::Amazon => width < height,
+        Piece::Bishop | Piece::Knight => true,
     }
 }
 
+/// The lexicographically smallest of the 8 rotations and reflections of the board.
+///
+/// The solutions with the same canonical form are symmetric to each other.
+/// The board is expected to be square and the pieces to be in the distinct rows, otherwise the chromosome
+/// is returned as is. With fewer pieces than the columns, only the images, that keep the pieces
+/// in the leftmost columns, are compared, which are the same for all the symmetric solutions
+pub fn canonical_form(chromosome: &Chromosome) -> Chromosome {
+    let genes = chromosome.genes();
+    let size = chromosome.width();
+    if size != chromosome.height() || check_rows(genes, size as usize).is_err() {
+        return chromosome.clone();
+    }
+    let last = size.saturating_sub(1);
+    let pieces = genes.len();
+
+    (0..8)
+        .filter_map(|symmetry: u8| {
+            let mut image = vec![0; pieces];
+            for (x, y) in genes.iter().enumerate() {
+                let (x, y) = (x as Gene, *y);
+                let (x, y) = if symmetry & 1 == 0 { (x, y) } else { (y, x) };
+                let x = if symmetry & 2 == 0 { x } else { last - x };
+                let y = if symmetry & 4 == 0 { y } else { last - y };
+                *image.get_mut(x as usize)? = y;
+            }
+            Some(image)
+        })
+        .min()
+        .map(|genes| {
+            Chromosome::with_height(genes, size)
+                .with_width(size)
+                .with_piece(chromosome.piece())
+        })
+        .unwrap()
+}
+
 impl<'ch> From<&'ch Chromosome> for Board<'ch> {
     #[inline]
     fn from(chromosome: &'ch Chromosome) -> Self {
@@ -61,82 +583,472 @@ impl<'ch> From<Board<'ch>> for &'ch Chromosome {
 }
 
 impl<'ch> fmt::Display for Board<'ch> {
+    #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let size = self.0.genes.len();
-
-        let mut color = false; // black or white
-        for y in 0..size as u16 {
-            for x in 0..size {
-                write!(
-                    f,
-                    "{}",
-                    if self.0.genes[x] == y {
-                        "##".dark_red()
-                            .on(if color { Color::DarkGrey } else { Color::White })
-                    } else {
-                        "  ".on(if color { Color::DarkGrey } else { Color::White })
-                    }
-                )?;
-                color = !color;
+        self.with_glyph(Glyph::default()).fmt(f)
+    }
+}
+
+struct WithGlyph<'b, 'ch>(&'b Board<'ch>, Glyph);
+
+impl<'b, 'ch> fmt::Display for WithGlyph<'b, 'ch> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let WithGlyph(board @ Board(chromosome), glyph) = self;
+
+        for y in 0..chromosome.height() {
+            for x in 0..chromosome.width() as usize {
+                let background = if is_dark_cell(x, y as usize) {
+                    Color::DarkGrey
+                } else {
+                    Color::White
+                };
+                if board.has_piece(x, y) {
+                    write!(f, "{}", glyph.cell().dark_red().on(background))?;
+                } else if chromosome.is_blocked(x as Gene, y) {
+                    write!(f, "{}", "XX".black().on(background))?;
+                } else {
+                    write!(f, "{}", "  ".on(background))?;
+                }
             }
             writeln!(f)?;
-            if size % 2 == 0 {
-                color = !color;
-            }
         }
         Ok(())
     }
 }
 
+/// Whether the cell in the column `x` and the row `y` is dark, as on a chessboard,
+/// regardless of the size of the board
+#[inline]
+fn is_dark_cell(x: usize, y: usize) -> bool {
+    (x + y).is_multiple_of(2)
+}
+
 #[cfg(test)]
 mod tests {
+    use std::{fmt::Write, sync::Arc};
+
+    use crossterm::style::{Color, Stylize};
     use pretty_assertions::assert_eq;
+    use rand::{rngs::StdRng, SeedableRng};
 
-    use crate::board::Board;
-    use crate::chromosome::Chromosome;
+    use crate::{
+        board::{
+            canonical_form, is_solvable, is_solvable_for, is_solvable_on, BlockedCells, Board,
+            Glyph, Piece,
+        },
+        chromosome::Chromosome,
+    };
 
     #[test]
-    fn test_beats_count() {
+    fn test_diagonal_beats_count_agrees() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for size in 1..40 {
+            for _ in 0..10 {
+                let chromosome = Chromosome::new(size, &mut rng);
+                let board = Board::from(&chromosome);
+                assert_eq!(
+                    board.pairwise_beats_count(Piece::Queen),
+                    board.diagonal_beats_count()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_solution() {
+        let solution = Chromosome::from(vec![1, 3, 0, 2]);
+        assert!(Board::from(&solution).is_solution());
+        assert!(!Board::from(&Chromosome::from(vec![0, 1, 2, 3])).is_solution());
+        assert!(!Board::from(&Chromosome::from(vec![0, 1, 3, 5, 2, 4])).is_solution());
+        // the knights do not attack along the diagonals
+        let knights = Chromosome::from(vec![0, 1, 2, 3]).with_piece(Piece::Knight);
+        assert!(Board::from(&knights).is_solution());
+
+        let blocked = Arc::new(BlockedCells::from_iter([(0, 1)]));
+        let solution = solution.with_blocked(Some(blocked));
+        assert!(!Board::from(&solution).is_solution());
+    }
+
+    #[test]
+    fn test_conflicting_columns() {
+        assert!(Board::from(&Chromosome::from(vec![1, 3, 0, 2]))
+            .conflicting_columns()
+            .is_empty());
         assert_eq!(
-            2,
-            Board::from(&Chromosome { genes: vec![0, 0] }).beats_count()
+            vec![0, 1],
+            Board::from(&Chromosome::from(vec![0, 1, 3, 5, 2, 4])).conflicting_columns()
         );
+        // the pieces, that share a row
         assert_eq!(
-            2,
-            Board::from(&Chromosome { genes: vec![0, 1] }).beats_count()
+            vec![0, 1],
+            Board::from(&Chromosome::from(vec![0, 0, 4, 1, 5, 2])).conflicting_columns()
         );
+        let mut rng = StdRng::seed_from_u64(42);
+        // both the sequential and the parallel counting
+        for size in [12, 40] {
+            for piece in [Piece::Queen, Piece::Knight, Piece::Bishop] {
+                let chromosome = Chromosome::new(size, &mut rng).with_piece(piece);
+                let board = Board::from(&chromosome);
+                assert_eq!(
+                    board.beats_count() as usize,
+                    board.conflicting_columns().len()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_rectangular_beats_count() {
+        // 4 columns and 6 rows
+        let board = Chromosome::with_height(vec![0, 2, 4, 1], 6);
+        assert_eq!(0, Board::from(&board).beats_count());
+        let board = Chromosome::with_height(vec![0, 5, 3, 4], 6);
+        assert_eq!(2, Board::from(&board).beats_count());
+        let board = Chromosome::with_height(vec![5, 0, 3, 5], 6);
+        assert_eq!(3, Board::from(&board).beats_count());
+
+        let mut rng = StdRng::seed_from_u64(42);
+        for (width, height) in [(4, 6), (6, 4), (1, 5), (10, 3)] {
+            for _ in 0..20 {
+                let chromosome = Chromosome::new_rectangular(width, height, &mut rng);
+                let board = Board::from(&chromosome);
+                assert_eq!(
+                    board.pairwise_beats_count(Piece::Queen),
+                    board.beats_count(),
+                    "{:?}",
+                    chromosome.genes()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_plain_rectangular() {
         assert_eq!(
-            0,
-            Board::from(&Chromosome { genes: vec![0, 2] }).beats_count()
+            "Q...\n...Q\n.Q..\n....\n..Q.\n....\n",
+            Board::from(&Chromosome::with_height(vec![0, 2, 4, 1], 6)).render_plain()
         );
+    }
+
+    #[test]
+    fn test_to_fen() {
+        let chromosome = Chromosome::from(vec![1, 3, 0, 2]);
+        assert_eq!("2Q1/Q3/3Q/1Q2", Board::from(&chromosome).to_fen());
+        let chromosome = chromosome.with_piece(Piece::Knight);
+        assert_eq!("2N1/N3/3N/1N2", Board::from(&chromosome).to_fen());
+        let chromosome = Chromosome::from(vec![0, 0, 11, 3, 4, 5, 6, 7, 8, 9, 10, 2]);
         assert_eq!(
-            2,
-            Board::from(&Chromosome { genes: vec![1, 0] }).beats_count()
+            "QQ10/12/11Q/3Q8/4Q7/5Q6/6Q5/7Q4/8Q3/9Q2/10Q1/2Q9",
+            Board::from(&chromosome).to_fen()
         );
+    }
+
+    #[test]
+    fn test_render_plain() {
         assert_eq!(
-            0,
-            Board::from(&Chromosome { genes: vec![2, 0] }).beats_count()
+            "..Q.\nQ...\n...Q\n.Q..\n",
+            Board::from(&Chromosome::from(vec![1, 3, 0, 2])).render_plain()
+        );
+    }
+
+    #[test]
+    fn test_parse_blocked_cells() {
+        let blocked: BlockedCells = "# corners\n0 0\n\n 3,3 \n1, 2\n".parse().unwrap();
+        assert_eq!(BlockedCells::from_iter([(0, 0), (3, 3), (1, 2)]), blocked);
+        assert!(blocked.contains(1, 2));
+        assert!(!blocked.contains(2, 1));
+        assert!(blocked.check_bounds(4, 4).is_ok());
+        assert!(blocked.check_bounds(4, 3).is_err());
+
+        assert!("0 0\n1".parse::<BlockedCells>().is_err());
+        assert!("0 0 0".parse::<BlockedCells>().is_err());
+        assert!("0 -1".parse::<BlockedCells>().is_err());
+        assert!("".parse::<BlockedCells>().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_blocked_cells_penalty() {
+        let solution = Chromosome::from(vec![1, 3, 0, 2]);
+        assert_eq!(1.0, solution.fitness());
+        let blocked = Arc::new(BlockedCells::from_iter([(0, 1), (1, 3), (2, 2)]));
+        let chromosome = solution.clone().with_blocked(Some(blocked));
+        // the blocked cells are not a part of the identity of the individual
+        assert_eq!(solution, chromosome);
+        assert_eq!(2, Board::from(&chromosome).blocked_count());
+        assert_eq!(0, Board::from(&chromosome).beats_count());
+        assert_eq!(1.0 / 3.0, chromosome.fitness());
+    }
+
+    #[test]
+    fn test_render_plain_blocked_cells() {
+        let blocked = Arc::new(BlockedCells::from_iter([(0, 0), (0, 1), (3, 3)]));
+        let chromosome = Chromosome::from(vec![1, 3, 0, 2]).with_blocked(Some(blocked));
+        let board = Board::from(&chromosome);
+        assert_eq!("x.Q.\nQ...\n...Q\n.Q.x\n", board.render_plain());
+        assert!(board.with_glyph(Glyph::Blocks).to_string().contains("XX"));
+    }
+
+    #[test]
+    fn test_render_plain_has_no_escapes() {
+        let chromosome = Chromosome::from(vec![1, 3, 0, 2]);
+        let board = Board::from(&chromosome);
+        assert!(!board.render_plain().contains('\x1b'));
+        // the checkerboard is still drawn in color
+        assert!(board.to_string().contains('\x1b'));
+    }
+
+    #[test]
+    fn test_with_glyph() {
+        let chromosome = Chromosome::from(vec![1, 3, 0, 2]);
+        let board = Board::from(&chromosome);
+        for (glyph, marker) in [
+            (Glyph::Unicode, "\u{265B}"),
+            (Glyph::Blocks, "##"),
+            (Glyph::Ascii, "Q"),
+        ] {
+            let rendered = board.with_glyph(glyph).to_string();
+            assert_eq!(4, rendered.matches(marker).count(), "{glyph:?}");
+            assert_eq!(4, rendered.lines().count());
+        }
+        assert_eq!(
+            board.to_string(),
+            board.with_glyph(Glyph::Blocks).to_string()
         );
+    }
+
+    #[test]
+    fn test_is_solvable_on() {
+        assert!(!is_solvable_on(Piece::Queen, 3, 3));
+        assert!(is_solvable_on(Piece::Queen, 3, 4));
+        assert!(is_solvable_on(Piece::Queen, 2, 3));
+        assert!(!is_solvable_on(Piece::Queen, 5, 4));
+        assert!(!is_solvable_on(Piece::Rook, 5, 4));
+        assert!(is_solvable_on(Piece::Bishop, 5, 1));
+        assert!(is_solvable_on(Piece::Knight, 5, 2));
+    }
+
+    #[test]
+    fn test_checkerboard_colors() {
+        for genes in [vec![1, 3, 0, 2], vec![0, 2, 4, 1, 3]] {
+            let chromosome = Chromosome::from(genes);
+            let mut expected = String::new();
+            for y in 0..chromosome.height() {
+                for (x, gene) in chromosome.genes().iter().enumerate() {
+                    let background = if (x + y as usize).is_multiple_of(2) {
+                        Color::DarkGrey
+                    } else {
+                        Color::White
+                    };
+                    if *gene == y {
+                        write!(expected, "{}", "##".dark_red().on(background)).unwrap();
+                    } else {
+                        write!(expected, "{}", "  ".on(background)).unwrap();
+                    }
+                }
+                expected.push('\n');
+            }
+            assert_eq!(expected, Board::from(&chromosome).to_string());
+        }
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_to_svg() {
+        let chromosome = Chromosome::from(vec![1, 3, 0, 2]);
+        let svg = Board::from(&chromosome).to_svg(10);
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"40\""));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(16, svg.matches("<rect ").count());
+        assert_eq!(4, svg.matches("<circle ").count());
+        assert!(svg.contains(r#"<circle cx="5" cy="15""#));
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_large_svg() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let chromosome = Chromosome::new(100, &mut rng);
+        let svg = Board::from(&chromosome).to_svg(8);
+        assert!(svg.starts_with(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="800" height="800" viewBox="0 0 800 800">"#
+        ));
+        assert_eq!(100 * 100, svg.matches("<rect ").count());
+        // one queen in every column
+        let columns: std::collections::HashSet<&str> = svg
+            .lines()
+            .filter_map(|line| line.strip_prefix("<circle cx=\""))
+            .filter_map(|line| line.split('"').next())
+            .collect();
+        assert_eq!(100, columns.len());
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_svg_cell_size_overflow() {
+        let chromosome = Chromosome::from(vec![1, 3, 0, 2]);
+        let svg = Board::from(&chromosome).to_svg(u32::MAX);
+        let side = u32::MAX / 4 * 4;
+        assert!(svg.starts_with(&format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{side}" height="{side}""#
+        )));
+        assert_eq!(16, svg.matches("<rect ").count());
+    }
+
+    #[test]
+    fn test_canonical_form() {
+        let solution = Chromosome::from(vec![0, 4, 7, 5, 2, 6, 1, 3]);
+        let mirrored = Chromosome::from(vec![3, 1, 6, 2, 5, 7, 4, 0]);
+        let rotated = Chromosome::from(vec![2, 5, 3, 1, 7, 4, 6, 0]);
+        let canonical = canonical_form(&solution);
+        assert_eq!(canonical, canonical_form(&mirrored));
+        assert_eq!(canonical, canonical_form(&rotated));
+        assert_eq!(1.0, canonical.fitness());
+
+        let other = Chromosome::from(vec![0, 5, 7, 2, 6, 3, 1, 4]);
+        assert_ne!(canonical, canonical_form(&other));
+    }
+
+    #[test]
+    fn test_canonical_form_of_fewer_pieces() {
+        // the first 3 columns of the solution [0, 2, 4, 1, 3] and their vertical reflection.
+        // The other images take the columns on the right, which are always empty
+        let solution = Chromosome::with_height(vec![4, 2, 0], 5).with_width(5);
+        let reflected = Chromosome::with_height(vec![0, 2, 4], 5).with_width(5);
+        let canonical = canonical_form(&solution);
+        assert_eq!((5, 5), (canonical.width(), canonical.height()));
+        assert_eq!([0, 2, 4], canonical.genes()[..]);
+        assert_eq!(canonical, canonical_form(&reflected));
+
+        let other = Chromosome::with_height(vec![1, 3, 0], 5).with_width(5);
+        assert_ne!(canonical, canonical_form(&other));
+        // the pieces in the top rows are transposed into the leftmost columns
+        let transposed = Chromosome::with_height(vec![2, 0, 1], 5).with_width(5);
+        assert_eq!([1, 2, 0], canonical_form(&transposed).genes()[..]);
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_save_png() {
+        let chromosome = Chromosome::from(vec![1, 3, 0, 2]);
+        let board = Board::from(&chromosome);
+        let path = std::env::temp_dir().join(format!("queen-placement-{}.png", std::process::id()));
+        board.save_png(&path, 10).unwrap();
+        let image = image::open(&path).unwrap().into_rgb8();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!((40, 40), image.dimensions());
+        // the queen in the center of the cell (0, 1), the empty cells around it
+        assert_eq!(image::Rgb([0x8B, 0x00, 0x00]), *image.get_pixel(5, 15));
+        assert_eq!(image::Rgb([0xA9, 0xA9, 0xA9]), *image.get_pixel(5, 5));
+        assert_eq!(image::Rgb([0xFF, 0xFF, 0xFF]), *image.get_pixel(15, 5));
+
+        assert!(board.save_png(&path, 10_000).is_err());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_is_solvable() {
+        assert!(is_solvable(1));
+        assert!(!is_solvable(2));
+        assert!(!is_solvable(3));
+        assert!((4..=100).all(is_solvable));
+    }
+
+    #[test]
+    fn test_is_solvable_for() {
+        fn permutations(n: u16) -> Vec<Vec<u16>> {
+            if n == 0 {
+                return vec![vec![]];
+            }
+            permutations(n - 1)
+                .into_iter()
+                .flat_map(|permutation| {
+                    (0..n).map(move |i| {
+                        let mut permutation = permutation.clone();
+                        permutation.insert(i as usize, n - 1);
+                        permutation
+                    })
+                })
+                .collect()
+        }
+
+        for n in 1..=7 {
+            let permutations = permutations(n);
+            for piece in [
+                Piece::Queen,
+                Piece::Rook,
+                Piece::Bishop,
+                Piece::Knight,
+                Piece::Amazon,
+            ] {
+                let solved = permutations.iter().any(|genes| {
+                    Board::from(&Chromosome::from(genes.clone())).beats_count_for(piece) == 0
+                });
+                assert_eq!(is_solvable_for(piece, n), solved, "{piece:?} on {n}x{n}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_bishops() {
+        let chromosome = Chromosome::from(vec![0, 1, 3, 2]).with_piece(Piece::Bishop);
+        // (0, 0) and (1, 1) share a diagonal, as well as (2, 3) and (3, 2)
+        assert_eq!(4, Board::from(&chromosome).beats_count());
+        let chromosome = Chromosome::from(vec![0, 2, 0, 2]).with_piece(Piece::Bishop);
+        // the same row is not a bishop move
+        assert_eq!(0, Board::from(&chromosome).beats_count());
+        assert_eq!(4, Board::from(&chromosome).beats_count_for(Piece::Queen));
+        assert_eq!(4, Board::from(&chromosome).beats_count_for(Piece::Rook));
+    }
+
+    #[test]
+    fn test_knights() {
+        let chromosome = Chromosome::from(vec![0, 2, 2]);
+        let board = Board::from(&chromosome);
+        // only (0, 0) and (1, 2) are a knight move apart
+        assert_eq!(2, board.beats_count_for(Piece::Knight));
+        // (2, 2) is on the diagonal of (0, 0) and in the row of (1, 2)
+        assert_eq!(3, board.beats_count_for(Piece::Amazon));
+        let chromosome = Chromosome::from(vec![0, 1, 2, 3]).with_piece(Piece::Knight);
+        assert_eq!(0, Board::from(&chromosome).beats_count());
+        assert_eq!(1.0, chromosome.fitness());
+    }
+
+    #[test]
+    fn test_same_row_beats_count() {
+        let chromosome = Chromosome::from(vec![3, 3, 0, 1]);
+        let board = Board::from(&chromosome);
+        // (0, 3) and (1, 3) share a row, (2, 0) and (3, 1) share a diagonal
+        assert_eq!(4, board.beats_count());
+        assert_eq!(2, board.beats_count_for(Piece::Rook));
+    }
+
+    #[test]
+    fn test_beats_count() {
+        assert_eq!(2, Board::from(&Chromosome::from(vec![0, 0])).beats_count());
+        assert_eq!(2, Board::from(&Chromosome::from(vec![0, 1])).beats_count());
+        assert_eq!(0, Board::from(&Chromosome::from(vec![0, 2])).beats_count());
+        assert_eq!(2, Board::from(&Chromosome::from(vec![1, 0])).beats_count());
+        assert_eq!(0, Board::from(&Chromosome::from(vec![2, 0])).beats_count());
         assert_eq!(
             2,
-            Board::from(&Chromosome {
-                genes: vec![0, 2, 1]
-            })
-            .beats_count()
+            Board::from(&Chromosome::from(vec![0, 2, 1])).beats_count()
         );
         assert_eq!(
             3,
-            Board::from(&Chromosome {
-                genes: vec![0, 2, 2]
-            })
-            .beats_count()
+            Board::from(&Chromosome::from(vec![0, 2, 2])).beats_count()
         );
         assert_eq!(
             0,
-            Board::from(&Chromosome {
-                genes: vec![0, 2, 4]
-            })
-            .beats_count()
+            Board::from(&Chromosome::from(vec![0, 2, 4])).beats_count()
+        );
+        assert_eq!(
+            3,
+            Board::from(&Chromosome::from(vec![1, 1, 3])).beats_count()
+        );
+        assert_eq!(
+            2,
+            Board::from(&Chromosome::from(vec![3, 0, 3])).beats_count()
         );
     }
 }
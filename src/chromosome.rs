@@ -1,20 +1,158 @@
-use rand::seq::SliceRandom;
-use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+use std::{
+    hash::{Hash, Hasher},
+    ops::RangeInclusive,
+    sync::{Arc, OnceLock},
+};
 
-use crate::selection::Generation;
+use anyhow::bail;
+use rand::{seq::SliceRandom, Rng};
+use rayon::prelude::{
+    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator,
+};
+
+use crate::{
+    board::{BlockedCells, Board, FixedPieces, Piece},
+    rng,
+    selection::{self, Generation, Probability},
+};
 
 pub type Gene = u16;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Amount of the genes, below which the chromosomes are processed by the sequential iterators,
+/// since the overhead of the parallel ones outweighs the work on the small boards (see `benches/sequential.rs`)
+pub(crate) const SEQUENTIAL_LEN: usize = 16;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Chromosome {
+    /// Row of the queen in each column of the board
     pub(crate) genes: Vec<Gene>,
+    /// Amount of the rows of the board, that has `genes.len()` columns
+    pub(crate) height: Gene,
+    /// Amount of the columns without a piece, to the right of the `genes.len()` columns with the pieces,
+    /// when fewer pieces than the columns are placed
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) empty_columns: Gene,
+    /// Piece, that is placed in every column
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) piece: Piece,
+    /// Cells, that the pieces must avoid, shared by the whole generation.
+    /// They are a part of the problem rather than of the solution, so they are neither compared nor hashed
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) blocked: Option<Arc<BlockedCells>>,
+    /// Pieces, that are kept in their rows through the crossover and the mutation, shared by the whole generation.
+    /// Not compared nor hashed, as the genes already contain them
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) fixed: Option<Arc<FixedPieces>>,
+    /// Cached [`Board::fitness`], that is reset whenever the genes are changed
+    #[cfg_attr(feature = "serde", serde(skip))]
+    fitness: OnceLock<f32>,
 }
 
 impl Chromosome {
-    pub fn new(base: u16) -> Self {
-        let mut genes: Vec<Gene> = (0..base).collect();
-        genes.shuffle(&mut rand::thread_rng());
-        Self { genes }
+    /// Random placement of queens on the `base`x`base` board, one per row and column
+    #[inline]
+    pub fn new<R: Rng + ?Sized>(base: u16, rng: &mut R) -> Self {
+        Self::new_rectangular(base, base, rng)
+    }
+
+    /// Random placement of `width` queens on the board of `width` columns and `height` rows,
+    /// one per column. The rows are distinct, if there are enough of them,
+    /// otherwise every row is used about the same amount of times
+    pub fn new_rectangular<R: Rng + ?Sized>(width: u16, height: u16, rng: &mut R) -> Self {
+        let mut genes: Vec<Gene> = (0..height)
+            .cycle()
+            .take(width.max(height) as usize)
+            .collect();
+        if width >= height {
+            genes.shuffle(rng);
+        } else {
+            let (sample, _) = genes.partial_shuffle(rng, width as usize);
+            genes = sample.to_vec();
+        }
+        Self::with_height(genes, height)
+    }
+
+    /// Chromosome of the square board, checking that the genes are a permutation of `0..genes.len()`
+    pub fn try_from_genes(genes: Vec<Gene>) -> anyhow::Result<Self> {
+        check_rows(&genes, genes.len())?;
+        Ok(genes.into())
+    }
+
+    /// Chromosome of the board with `genes.len()` columns and `height` rows
+    #[inline]
+    pub fn with_height(genes: Vec<Gene>, height: Gene) -> Self {
+        Self {
+            genes,
+            height,
+            empty_columns: 0,
+            piece: Piece::default(),
+            blocked: None,
+            fixed: None,
+            fitness: OnceLock::new(),
+        }
+    }
+
+    /// Replaces the pieces on the board, which invalidates the cached fitness
+    #[inline]
+    pub fn with_piece(mut self, piece: Piece) -> Self {
+        self.fitness.take();
+        self.piece = piece;
+        self
+    }
+
+    /// Widens the board to `width` columns, that is at least the amount of the genes,
+    /// by appending the columns without a piece.
+    ///
+    /// The pieces still have a distinct column each, and a distinct row each, while there are not more of them
+    /// than the rows: the random chromosomes draw the distinct rows, the crossover keeps the rows of the parents
+    /// and draws the rest of them from the whole board, and the mutations only move the rows between the pieces.
+    /// Since the columns of any solution, that has the pieces in every column, are a solution of their own,
+    /// searching only the leftmost columns does not lose the solvability
+    #[inline]
+    pub fn with_width(mut self, width: u16) -> Self {
+        debug_assert!(
+            width as usize >= self.genes.len(),
+            "The board is narrower than the genes"
+        );
+        self.empty_columns = width.saturating_sub(self.genes.len() as Gene);
+        self
+    }
+
+    /// Replaces the blocked cells of the board, which invalidates the cached fitness
+    #[inline]
+    pub fn with_blocked(mut self, blocked: Option<Arc<BlockedCells>>) -> Self {
+        self.fitness.take();
+        self.blocked = blocked;
+        self
+    }
+
+    /// Moves the `fixed` pieces into their rows and keeps them there from now on
+    #[inline]
+    pub fn with_fixed(mut self, fixed: Option<Arc<FixedPieces>>) -> Self {
+        self.fixed = fixed;
+        self.pin();
+        self
+    }
+
+    /// Puts every fixed piece back into its row by swapping the rows with the column, that has taken it,
+    /// so the genes remain a permutation. The rows of the fixed pieces are distinct,
+    /// so the swap never moves the pieces, that are already pinned
+    pub(crate) fn pin(&mut self) {
+        let Some(fixed) = self.fixed.clone() else {
+            return;
+        };
+        for (x, y) in fixed.iter() {
+            let x = x as usize;
+            if self.genes[x] == y {
+                continue;
+            }
+            match self.genes.iter().position(|gene| *gene == y) {
+                Some(taken) => self.genes_mut().swap(x, taken),
+                // the row is free on the taller board
+                None => self.genes_mut()[x] = y,
+            }
+        }
     }
 
     #[inline]
@@ -22,32 +160,541 @@ impl Chromosome {
         &self.genes
     }
 
-    pub fn create_generation(board_size: u16, population_size: usize) -> Generation {
-        (0..population_size)
-            .into_par_iter()
-            .map(|_| Self::new(board_size))
-            .collect()
+    /// Whether the genes are exactly the numbers from `0` to `genes.len() - 1`,
+    /// as the ones of [`Chromosome::try_from_genes`], without the duplicate or out-of-range rows
+    #[inline]
+    pub fn is_permutation(&self) -> bool {
+        is_permutation(&self.genes)
+    }
+
+    /// Amount of the columns of the board, including the ones without a piece
+    #[inline]
+    pub fn width(&self) -> u16 {
+        self.genes.len() as u16 + self.empty_columns
+    }
+
+    /// Amount of the rows of the board
+    #[inline]
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    #[inline]
+    pub fn piece(&self) -> Piece {
+        self.piece
+    }
+
+    #[inline]
+    pub fn blocked(&self) -> Option<&BlockedCells> {
+        self.blocked.as_deref()
+    }
+
+    #[inline]
+    pub fn fixed(&self) -> Option<&FixedPieces> {
+        self.fixed.as_deref()
+    }
+
+    /// Whether no piece may occupy the cell in the column `x` and the row `y`
+    #[inline]
+    pub fn is_blocked(&self, x: Gene, y: Gene) -> bool {
+        self.blocked().is_some_and(|blocked| blocked.contains(x, y))
+    }
+
+    /// Mutable access to the genes, that invalidates the cached fitness
+    #[inline]
+    pub fn genes_mut(&mut self) -> &mut [Gene] {
+        self.fitness.take();
+        &mut self.genes
+    }
+
+    /// Swaps two random distinct positions, so the genes remain a permutation.
+    /// Less than two genes are left unchanged
+    pub fn mutate_swap<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        if self.genes.len() < 2 {
+            return;
+        }
+        let positions = rand::seq::index::sample(rng, self.genes.len(), 2);
+        self.genes_mut()
+            .swap(positions.index(0), positions.index(1));
+    }
+
+    /// Reverses a random segment of at least two genes, so the genes remain a permutation.
+    /// Less than two genes are left unchanged
+    #[inline]
+    pub fn mutate_invert<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        if self.genes.len() < 2 {
+            return;
+        }
+        let segment = selection::random_segment(self.genes.len(), rng);
+        self.invert(segment);
+    }
+
+    /// Reverses the genes at the `segment` of positions
+    #[inline]
+    pub fn invert(&mut self, segment: RangeInclusive<usize>) {
+        self.genes_mut()[segment].reverse();
+    }
+
+    /// Swaps every gene with a random other one with the probability `gene_probability`,
+    /// so the genes remain a permutation. Returns the amount of the swaps
+    pub fn mutate_genes<R: Rng + ?Sized>(
+        &mut self,
+        gene_probability: Probability,
+        rng: &mut R,
+    ) -> usize {
+        let len = self.genes.len();
+        if len < 2 {
+            return 0;
+        }
+        let mut swaps = 0;
+        for i in 0..len {
+            if rng.gen::<Probability>() < gene_probability {
+                // a random position, other than `i`
+                let j = (i + rng.gen_range(1..len)) % len;
+                self.genes_mut().swap(i, j);
+                swaps += 1;
+            }
+        }
+        swaps
+    }
+
+    /// Swaps a random beaten piece with a random other column, which wastes no moves on the pieces,
+    /// that are already safe. Falls back to [`Chromosome::mutate_swap`], if no piece is beaten
+    pub fn mutate_guided<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        let Some(&conflicting) = Board::from(&*self).conflicting_columns().choose(rng) else {
+            self.mutate_swap(rng);
+            return;
+        };
+        let len = self.genes.len();
+        let other = (conflicting + rng.gen_range(1..len)) % len;
+        self.genes_mut().swap(conflicting, other);
+    }
+
+    /// Moves a random gene to another random position, so the genes remain a permutation.
+    /// Less than two genes are left unchanged
+    #[inline]
+    pub fn mutate_insert<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        if self.genes.len() < 2 {
+            return;
+        }
+        let positions = rand::seq::index::sample(rng, self.genes.len(), 2);
+        self.insert(positions.index(0), positions.index(1));
+    }
+
+    /// Removes the gene at the position `from` and reinserts it at the position `to`,
+    /// shifting the genes in between towards `from`
+    pub fn insert(&mut self, from: usize, to: usize) {
+        if from < to {
+            self.genes_mut()[from..=to].rotate_left(1);
+        } else if to < from {
+            self.genes_mut()[to..=from].rotate_right(1);
+        }
+    }
+
+    /// Returns the fitness of the [`Board`] of this [`Chromosome`], computing it only once
+    #[inline]
+    pub fn fitness(&self) -> f32 {
+        *self
+            .fitness
+            .get_or_init(|| Board::from(self).uncached_fitness())
+    }
+
+    /// Amount of the positions, where the genes differ.
+    ///
+    /// Both chromosomes are expected to have the same amount of genes
+    pub fn hamming_distance(&self, other: &Self) -> usize {
+        debug_assert_eq!(
+            self.genes.len(),
+            other.genes.len(),
+            "The chromosomes of different lengths"
+        );
+        if self.genes.len() < SEQUENTIAL_LEN {
+            self.genes
+                .iter()
+                .zip(&other.genes)
+                .filter(|(g1, g2)| g1 != g2)
+                .count()
+        } else {
+            self.genes
+                .par_iter()
+                .zip(&other.genes)
+                .filter(|(g1, g2)| g1 != g2)
+                .count()
+        }
+    }
+
+    #[inline]
+    pub fn create_generation<R: Rng + ?Sized>(
+        board_size: u16,
+        population_size: usize,
+        rng: &mut R,
+    ) -> Generation {
+        Self::create_rectangular_generation(board_size, board_size, population_size, rng)
+    }
+
+    /// Generation of [`Chromosome::new_rectangular`] individuals
+    pub fn create_rectangular_generation<R: Rng + ?Sized>(
+        width: u16,
+        height: u16,
+        population_size: usize,
+        rng: &mut R,
+    ) -> Generation {
+        let rngs = rng::fork(rng, population_size);
+        let new = |mut rng| Self::new_rectangular(width, height, &mut rng);
+        if (width as usize) < SEQUENTIAL_LEN {
+            rngs.into_iter().map(new).collect()
+        } else {
+            rngs.into_par_iter().map(new).collect()
+        }
     }
 }
 
+/// Checks, that the genes are distinct rows within `0..rows`.
+/// With as many rows as there are genes, they are a permutation of `0..genes.len()`
+pub(crate) fn check_rows(genes: &[Gene], rows: usize) -> anyhow::Result<()> {
+    let mut positions = vec![None; rows];
+    for (i, gene) in genes.iter().enumerate() {
+        let Some(position) = positions.get_mut(*gene as usize) else {
+            bail!("Gene {gene} at position {i} is out of range 0..{rows}");
+        };
+        if let Some(first) = position.replace(i) {
+            bail!("Gene {gene} is repeated at positions {first} and {i}");
+        }
+    }
+    Ok(())
+}
+
+/// Whether the genes are all the numbers from `0` to `genes.len() - 1`
+#[inline]
+pub(crate) fn is_permutation(genes: &[Gene]) -> bool {
+    check_rows(genes, genes.len()).is_ok()
+}
+
+/// Chromosome of the square board without any checks of the genes.
+///
+/// Meant for the internal use and the tests, see [`Chromosome::try_from_genes`] for the checked construction
+/// and [`Chromosome::is_permutation`] for checking the genes afterwards
 impl From<Vec<Gene>> for Chromosome {
     #[inline]
     fn from(genes: Vec<Gene>) -> Self {
-        Self { genes }
+        let height = genes.len() as Gene;
+        Self::with_height(genes, height)
+    }
+}
+
+impl PartialEq for Chromosome {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.height == other.height
+            && self.empty_columns == other.empty_columns
+            && self.piece == other.piece
+            && self.genes == other.genes
+    }
+}
+
+impl Eq for Chromosome {}
+
+impl Hash for Chromosome {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.genes.hash(state);
+        self.height.hash(state);
+        self.empty_columns.hash(state);
+        self.piece.hash(state);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
+    use rand::{rngs::StdRng, SeedableRng};
 
     use super::*;
 
     #[test]
     fn test_gene_new() {
         let len = 5;
-        let chromosome = Chromosome::new(len);
+        let chromosome = Chromosome::new(len, &mut rand::thread_rng());
         println!("{chromosome:#?}");
         assert_eq!(chromosome.genes.len(), len as usize);
     }
+
+    #[test]
+    fn test_mutate_swap() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for len in [2, 3, 8, 50] {
+            let original = Chromosome::new(len, &mut rng);
+            let mut mutated = original.clone();
+            assert_eq!(original.fitness(), mutated.fitness());
+            mutated.mutate_swap(&mut rng);
+
+            assert_eq!(2, original.hamming_distance(&mutated));
+            let mut genes = mutated.genes.clone();
+            genes.sort_unstable();
+            assert_eq!((0..len).collect::<Vec<_>>(), genes);
+            // the cached fitness is reset
+            assert_eq!(Board::from(&mutated).uncached_fitness(), mutated.fitness());
+        }
+    }
+
+    #[test]
+    fn test_mutate_single_gene() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for genes in [vec![], vec![0]] {
+            let original = Chromosome::from(genes);
+            let mut mutated = original.clone();
+            mutated.mutate_swap(&mut rng);
+            mutated.mutate_guided(&mut rng);
+            mutated.mutate_invert(&mut rng);
+            mutated.mutate_insert(&mut rng);
+            assert_eq!(original, mutated);
+        }
+    }
+
+    #[test]
+    fn test_invert() {
+        let original = Chromosome::from(vec![0, 1, 2, 3, 4, 5]);
+        for (segment, expected) in [
+            (0..=1, [1, 0, 2, 3, 4, 5]),
+            (4..=5, [0, 1, 2, 3, 5, 4]),
+            (0..=3, [3, 2, 1, 0, 4, 5]),
+            (2..=5, [0, 1, 5, 4, 3, 2]),
+            (0..=5, [5, 4, 3, 2, 1, 0]),
+        ] {
+            let mut inverted = original.clone();
+            inverted.invert(segment.clone());
+            assert_eq!(expected, inverted.genes[..], "{segment:?}");
+        }
+    }
+
+    #[test]
+    fn test_mutate_genes() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut chromosome = Chromosome::new(100, &mut rng);
+        let runs = 200;
+        let mut swaps = 0;
+        for _ in 0..runs {
+            swaps += chromosome.mutate_genes(Probability(0.1), &mut rng);
+            let mut genes = chromosome.genes.clone();
+            genes.sort_unstable();
+            assert_eq!((0..100).collect::<Vec<_>>(), genes);
+        }
+        // 10 swaps are expected per chromosome, with the standard deviation of 3
+        let mean = swaps as f32 / runs as f32;
+        assert!((mean - 10.0).abs() < 1.0, "{mean}");
+
+        let original = chromosome.clone();
+        assert_eq!(0, chromosome.mutate_genes(Probability(0.0), &mut rng));
+        assert_eq!(original, chromosome);
+        assert_eq!(100, chromosome.mutate_genes(Probability(1.0), &mut rng));
+    }
+
+    #[test]
+    fn test_with_fixed() {
+        let fixed = Arc::new("0:2,3:0,4:4".parse::<FixedPieces>().unwrap());
+        let chromosome = Chromosome::from(vec![0, 1, 2, 3, 4, 5]).with_fixed(Some(fixed.clone()));
+        assert_eq!([2, 1, 3, 0, 4, 5], chromosome.genes[..]);
+
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..20 {
+            let chromosome = Chromosome::new(8, &mut rng).with_fixed(Some(fixed.clone()));
+            assert!(fixed.iter().all(|(x, y)| chromosome.genes[x as usize] == y));
+            let mut genes = chromosome.genes.clone();
+            genes.sort_unstable();
+            assert_eq!((0..8).collect::<Vec<_>>(), genes);
+        }
+
+        // the fixed rows are free on the taller board
+        let chromosome = Chromosome::with_height(vec![0, 1, 2], 6)
+            .with_fixed(Some(Arc::new("1:5".parse().unwrap())));
+        assert_eq!([0, 5, 2], chromosome.genes[..]);
+    }
+
+    #[test]
+    fn test_mutate_guided() {
+        let mut rng = StdRng::seed_from_u64(42);
+        // only the queens in the columns 0 and 1 beat each other
+        let original = Chromosome::from(vec![0, 1, 3, 5, 2, 4]);
+        assert_eq!(vec![0, 1], Board::from(&original).conflicting_columns());
+        for _ in 0..50 {
+            let mut mutated = original.clone();
+            mutated.mutate_guided(&mut rng);
+            let changed: Vec<usize> = (0..6)
+                .filter(|x| mutated.genes[*x] != original.genes[*x])
+                .collect();
+            assert_eq!(2, changed.len());
+            assert!(changed.contains(&0) || changed.contains(&1), "{changed:?}");
+            assert_eq!(Board::from(&mutated).uncached_fitness(), mutated.fitness());
+        }
+
+        // a solution is still mutated
+        let mut solution = Chromosome::from(vec![1, 3, 0, 2]);
+        solution.mutate_guided(&mut rng);
+        assert_eq!(
+            2,
+            solution.hamming_distance(&Chromosome::from(vec![1, 3, 0, 2]))
+        );
+    }
+
+    #[test]
+    fn test_insert() {
+        let original = Chromosome::from(vec![0, 1, 2, 3, 4, 5]);
+        for (from, to, expected) in [
+            // after the source
+            (1, 4, [0, 2, 3, 4, 1, 5]),
+            (0, 5, [1, 2, 3, 4, 5, 0]),
+            (2, 3, [0, 1, 3, 2, 4, 5]),
+            // before the source
+            (4, 1, [0, 4, 1, 2, 3, 5]),
+            (5, 0, [5, 0, 1, 2, 3, 4]),
+            // in place
+            (3, 3, [0, 1, 2, 3, 4, 5]),
+        ] {
+            let mut inserted = original.clone();
+            inserted.insert(from, to);
+            assert_eq!(expected, inserted.genes[..], "{from} -> {to}");
+        }
+    }
+
+    #[test]
+    fn test_mutate_insert() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for len in [2, 3, 8, 50] {
+            let original = Chromosome::new(len, &mut rng);
+            let mut mutated = original.clone();
+            assert_eq!(original.fitness(), mutated.fitness());
+            mutated.mutate_insert(&mut rng);
+
+            assert_ne!(original, mutated);
+            let mut genes = mutated.genes.clone();
+            genes.sort_unstable();
+            assert_eq!((0..len).collect::<Vec<_>>(), genes);
+            // the cached fitness is reset
+            assert_eq!(Board::from(&mutated).uncached_fitness(), mutated.fitness());
+        }
+    }
+
+    #[test]
+    fn test_mutate_invert_is_permutation() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for len in [2, 3, 8] {
+            let mut chromosome = Chromosome::new(len, &mut rng);
+            for _ in 0..50 {
+                let before = chromosome.clone();
+                chromosome.mutate_invert(&mut rng);
+                assert_ne!(before, chromosome);
+                let mut genes = chromosome.genes.clone();
+                genes.sort_unstable();
+                assert_eq!((0..len).collect::<Vec<_>>(), genes);
+            }
+        }
+    }
+
+    #[test]
+    fn test_create_generation_seeded() {
+        let generation1 = Chromosome::create_generation(8, 50, &mut StdRng::seed_from_u64(42));
+        let generation2 = Chromosome::create_generation(8, 50, &mut StdRng::seed_from_u64(42));
+        assert_eq!(generation1, generation2);
+    }
+
+    #[test]
+    fn test_hamming_distance() {
+        let chromosome = Chromosome::from(vec![0, 1, 2, 3, 4]);
+        assert_eq!(0, chromosome.hamming_distance(&chromosome));
+        let swapped = Chromosome::from(vec![0, 3, 2, 1, 4]);
+        assert_eq!(2, chromosome.hamming_distance(&swapped));
+        assert_eq!(2, swapped.hamming_distance(&chromosome));
+        let shifted = Chromosome::from(vec![1, 2, 3, 4, 0]);
+        assert_eq!(5, chromosome.hamming_distance(&shifted));
+        assert_eq!(
+            0,
+            Chromosome::from(vec![]).hamming_distance(&Chromosome::from(vec![]))
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "different lengths")]
+    fn test_hamming_distance_of_different_lengths() {
+        Chromosome::from(vec![0, 1]).hamming_distance(&Chromosome::from(vec![0, 1, 2]));
+    }
+
+    #[test]
+    fn test_is_permutation() {
+        assert!(Chromosome::from(vec![1, 3, 0, 2]).is_permutation());
+        assert!(Chromosome::from(vec![]).is_permutation());
+        // duplicate
+        assert!(!Chromosome::from(vec![3, 1, 3, 0]).is_permutation());
+        // out of range
+        assert!(!Chromosome::from(vec![0, 4, 1, 2]).is_permutation());
+        assert!(Chromosome::new(20, &mut rand::thread_rng()).is_permutation());
+    }
+
+    #[test]
+    fn test_try_from_genes() {
+        assert_eq!(
+            Chromosome::from(vec![1, 3, 0, 2]),
+            Chromosome::try_from_genes(vec![1, 3, 0, 2]).unwrap()
+        );
+        assert!(Chromosome::try_from_genes(vec![]).is_ok());
+        assert_eq!(
+            "Gene 3 is repeated at positions 0 and 2",
+            Chromosome::try_from_genes(vec![3, 1, 3, 0])
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "Gene 4 at position 1 is out of range 0..4",
+            Chromosome::try_from_genes(vec![0, 4, 1, 2])
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_fitness_cache_invalidation() {
+        let mut chromosome = Chromosome::from(vec![1, 3, 0, 2]);
+        assert_eq!(1.0, chromosome.fitness());
+        chromosome.genes_mut().swap(0, 1);
+        assert_eq!(
+            Board::from(&chromosome).uncached_fitness(),
+            chromosome.fitness()
+        );
+        assert!(chromosome.fitness() < 1.0);
+    }
+
+    #[test]
+    fn test_new_rectangular() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut rows_used = [false; 6];
+        for _ in 0..100 {
+            let chromosome = Chromosome::new_rectangular(4, 6, &mut rng);
+            assert_eq!((4, 6), (chromosome.width(), chromosome.height()));
+            let genes = chromosome.genes();
+            assert!(genes.iter().all(|gene| *gene < 6), "{genes:?}");
+            let mut distinct = genes.to_vec();
+            distinct.sort_unstable();
+            distinct.dedup();
+            assert_eq!(4, distinct.len(), "{genes:?}");
+            genes
+                .iter()
+                .for_each(|gene| rows_used[*gene as usize] = true);
+        }
+        assert_eq!([true; 6], rows_used);
+
+        let chromosome = Chromosome::new_rectangular(6, 4, &mut rng);
+        assert_eq!((6, 4), (chromosome.width(), chromosome.height()));
+        let mut genes = chromosome.genes().to_vec();
+        genes.sort_unstable();
+        assert_eq!(vec![0, 0, 1, 1, 2, 3], genes);
+    }
+
+    #[test]
+    fn test_new_square_unchanged() {
+        let mut rng1 = StdRng::seed_from_u64(42);
+        let mut rng2 = StdRng::seed_from_u64(42);
+        let mut genes: Vec<Gene> = (0..8).collect();
+        genes.shuffle(&mut rng2);
+        assert_eq!(Chromosome::from(genes), Chromosome::new(8, &mut rng1));
+    }
 }
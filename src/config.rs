@@ -1,27 +1,641 @@
+use anyhow::{bail, ensure};
 use clap::{builder::PossibleValue, Parser, ValueEnum};
-use std::num::NonZeroU16;
-use std::{fmt, num::NonZeroUsize, str::FromStr};
+use rand::SeedableRng;
+use std::num::{NonZeroU16, NonZeroU32};
+use std::{fmt, num::NonZeroUsize, path::PathBuf, str::FromStr, time::Duration};
 
-use crate::selection::{Probability, SelectionStrategy};
+use crate::board::{BlockedCells, FixedPieces, Glyph, Piece};
+use crate::rng::SolverRng;
+use crate::scaling::FitnessScaling;
+use crate::selection::{
+    CrossoverStrategy, GenerationGap, MutationStrategy, Probability, RankPressure, SelectionParams,
+    SelectionStrategy,
+};
+use crate::solver::{AdaptiveMutation, Schedule};
 
-#[derive(Parser, Debug)]
+/// Representation of the boards, printed by the binary
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OutputFormat {
+    /// Colored cells with the glyphs
+    #[default]
+    Pretty,
+    /// `Q` for a queen and `.` for an empty cell
+    Ascii,
+    /// Placement of the pieces in the FEN notation
+    Fen,
+    /// Genes together with the amount of the beaten pieces and the fitness
+    Json,
+}
+
+#[derive(Parser, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[command(author, about)]
 pub struct Config {
-    /// Size of the chess board
+    /// Size of the square chess board. Shorthand for the same `--board-width` and `--board-height`
     #[arg(short, long, default_value_t = NonZeroU16::try_from(8).unwrap())]
     pub board_size: NonZeroU16,
 
-    /// Size of the population in one generation
-    #[arg(short, long, default_value_t = NonZeroUsize::try_from(100).unwrap())]
+    /// Amount of the columns of the board, and of the pieces, unless `--queens` are given. Overrides the board size
+    #[arg(long)]
+    pub board_width: Option<NonZeroU16>,
+
+    /// Amount of the rows of the board. Overrides the board size
+    #[arg(long)]
+    pub board_height: Option<NonZeroU16>,
+
+    /// Amount of the pieces, up to both the board width and height. They are searched on the leftmost columns
+    /// of the board, which is still solvable, since the same columns of a full solution are one.
+    /// Every piece keeps a distinct row through the crossover, that draws the missing rows from the whole board,
+    /// and the mutations, that only move the rows between the pieces
+    #[arg(long)]
+    pub queens: Option<NonZeroU16>,
+
+    /// Chess piece, that is placed in every column
+    #[arg(long, default_value_t = Piece::Queen)]
+    pub piece: Piece,
+
+    /// File with the cells, that no piece may occupy: one `x y` or `x,y` pair of the column and the row per line.
+    /// A piece on a blocked cell is penalized as a beaten one. Loaded by [`Config::blocked_cells`]
+    #[arg(long)]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub blocked_file: Option<PathBuf>,
+
+    /// Pieces, pinned to their rows, as the comma-separated `column:row` pairs, e.g. `0:3,5:1`.
+    /// The rest of the board is searched around them, but they may leave no solution, so the search may need a limit
+    #[arg(long)]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fixed: Option<FixedPieces>,
+
+    /// Size of the population in one generation (μ of the (μ+λ) and (μ,λ) selection)
+    #[arg(short, long, visible_alias = "mu", default_value_t = NonZeroUsize::try_from(100).unwrap())]
     pub generation_size: NonZeroUsize,
 
     /// Probability of mutation
     #[arg(short = 'p', long, default_value_t = Probability(0.1))]
     pub mutation_probability: Probability,
 
+    /// Bounds of the mutation probability like `0.05..0.5`, that replaces the constant one.
+    /// The less diverse the generation is, the closer the probability is to the upper bound
+    #[arg(long)]
+    pub adaptive_mutation: Option<AdaptiveMutation>,
+
+    /// Decay of the mutation probability, that replaces the constant one: from 0.3 to 0.05 evenly
+    /// in 5000 generations, like `linear:0.3..0.05@5000`, or by the factor of 0.999 every generation,
+    /// like `exp:0.3,0.999`
+    #[arg(long, conflicts_with = "adaptive_mutation")]
+    pub mutation_schedule: Option<Schedule>,
+
+    /// Probability of every gene of the offspring to swap with a random other one,
+    /// in addition to the mutation of the whole chromosome
+    #[arg(long, default_value_t = Probability(0.0))]
+    pub gene_mutation_probability: Probability,
+
     /// Strategy for selecting the best individuals for the next generation
     #[arg(short, long, default_value_t = SelectionStrategy::AdamAndEve)]
     pub selection_strategy: SelectionStrategy,
+
+    /// Amount of individuals competing in one tournament (used by the tournament and steady-state selection)
+    #[arg(long, default_value_t = NonZeroUsize::try_from(3).unwrap())]
+    pub tournament_size: NonZeroUsize,
+
+    /// Probability that the best participant of a tournament wins it
+    #[arg(long, default_value_t = Probability(1.0))]
+    pub tournament_pressure: Probability,
+
+    /// Selection pressure of the rank selection, within the range [1.0; 2.0]
+    #[arg(long, default_value_t = RankPressure(1.5))]
+    pub rank_pressure: RankPressure,
+
+    /// Amount of the best individuals that survive into the next generation unchanged.
+    /// With at least one, the best fitness never decreases
+    #[arg(short, long, default_value_t = 2)]
+    pub elitism: usize,
+
+    /// Operator, that combines the parents into a child
+    #[arg(short, long, visible_alias = "crossover", default_value_t = CrossoverStrategy::SimilarGenes)]
+    pub crossover_strategy: CrossoverStrategy,
+
+    /// Probability to cross the parents. Otherwise the child is a clone of the fitter parent,
+    /// that is still mutated with the mutation probability
+    #[arg(long, default_value_t = Probability(0.9))]
+    pub crossover_probability: Probability,
+
+    /// Amount of the parents of every child. More than two are supported only by the majority crossover
+    #[arg(long, default_value_t = 2)]
+    pub parents: usize,
+
+    /// Operator, applied to the offspring on mutation
+    #[arg(short, long, visible_alias = "mutation", default_value_t = MutationStrategy::Swap)]
+    pub mutation_strategy: MutationStrategy,
+
+    /// Transformation of the fitness before the fitness-proportional selection (roulette and SUS)
+    #[arg(long, default_value_t = FitnessScaling::None)]
+    pub fitness_scaling: FitnessScaling,
+
+    /// Amount of standard deviations below the mean fitness, that is scaled to zero by the sigma scaling
+    #[arg(long, default_value_t = 2.0)]
+    pub sigma_factor: f32,
+
+    /// Hamming distance, within which the individuals share their fitness
+    /// in the roulette, SUS and rank selection. `0` disables the sharing
+    #[arg(long, default_value_t = 0)]
+    pub sharing_radius: usize,
+
+    /// Amount of the worst individuals replaced in one step of the steady-state selection
+    #[arg(long, default_value_t = NonZeroUsize::try_from(2).unwrap())]
+    pub offspring_per_step: NonZeroUsize,
+
+    /// Fraction of the non-elite individuals replaced by the random ones after each generation
+    #[arg(long, default_value_t = Probability(0.0))]
+    pub immigrant_rate: Probability,
+
+    /// Amount of the offspring in the (μ+λ) and (μ,λ) selection. The latter requires λ ≥ μ
+    #[arg(long, default_value_t = NonZeroUsize::try_from(200).unwrap())]
+    pub lambda: NonZeroUsize,
+
+    /// Fraction of the generation, within the range (0.0; 1.0], that is replaced by the offspring.
+    /// The fittest rest is carried over unchanged
+    #[arg(long, default_value_t = GenerationGap(1.0))]
+    pub generation_gap: GenerationGap,
+
+    /// Amount of the distinct solutions to search for. They are collected from all the generations.
+    /// 1 by default, or unlimited with `--collect-all`
+    #[arg(long)]
+    pub solutions_wanted: Option<NonZeroUsize>,
+
+    /// Keep collecting the distinct solutions until `--solutions-wanted` of them are found,
+    /// or until the search is stopped by `--max-generations`, `--time-limit` or `--stagnation-generations`
+    #[arg(long)]
+    pub collect_all: bool,
+
+    /// Count the solutions, that are rotations or reflections of each other, only once
+    #[arg(long)]
+    pub unique_up_to_symmetry: bool,
+
+    /// Maximum amount of generations to search the solution in. Unlimited, if not specified
+    #[arg(long)]
+    pub max_generations: Option<NonZeroU32>,
+
+    /// Maximum time to search the solution for, like `500ms`, `30s`, `2m` or `1h`. Unlimited, if not specified
+    #[arg(long, value_parser = parse_duration)]
+    pub time_limit: Option<Duration>,
+
+    /// Amount of the generations without improvement of the best fitness, after which the search stops.
+    /// Unlimited, if not specified
+    #[arg(long)]
+    pub stagnation_generations: Option<NonZeroU32>,
+
+    /// Minimal increase of the best fitness, that is considered an improvement
+    #[arg(long, default_value_t = 0.0)]
+    pub stagnation_epsilon: f32,
+
+    /// Seed of the random number generator. Random, if not specified.
+    /// The parallel tasks draw from their own generators, forked from the seeded one,
+    /// so the runs with the same seed and arguments produce the same generations
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Run the search on a single thread, so that it does not depend on the thread scheduling at all
+    #[arg(long)]
+    pub deterministic: bool,
+
+    /// Amount of the threads to run the search on, instead of one per CPU.
+    /// The search runs in its own thread pool, so that several of them may share the machine
+    #[arg(long, conflicts_with = "deterministic")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub threads: Option<NonZeroUsize>,
+
+    /// Print the boards as plain text without colors and the live progress.
+    /// Turns the `pretty` output format into `ascii`. Implied, if the output is not a terminal
+    #[arg(long)]
+    pub no_color: bool,
+
+    /// Do not show the live progress, only the solutions. It is also hidden, if the output is not a terminal
+    #[arg(short, long, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Print the statistics of every generation as a plain line instead of the live progress
+    #[arg(short, long)]
+    pub verbose: bool,
+
+    /// Amount of the generations between the redraws of the live progress
+    #[arg(long, default_value_t = NonZeroU32::try_from(1).unwrap())]
+    pub refresh_every: NonZeroU32,
+
+    /// Representation of the printed boards. `json` requires the `serde` feature
+    #[arg(long, default_value_t = OutputFormat::Pretty)]
+    pub output_format: OutputFormat,
+
+    /// Marker of the queens on the colored boards. Falls back to `blocks`,
+    /// if the terminal does not support Unicode
+    #[arg(long, default_value_t = Glyph::Blocks)]
+    pub glyph: Glyph,
+
+    /// SVG file to draw the first found solution, or the best board if there is none, into
+    #[cfg(feature = "svg")]
+    #[arg(long)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub export_svg: Option<PathBuf>,
+
+    /// File to append the fitness statistics of every generation into, one JSON object per line.
+    /// Requires the `serde` feature
+    #[arg(long)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub stats_json: Option<PathBuf>,
+
+    /// CSV file to append the fitness statistics of every generation into.
+    /// The header row is written into the new or empty file only
+    #[arg(long)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub stats_csv: Option<PathBuf>,
+
+    /// File to periodically save the state of the search into. Requires the `serde` feature
+    #[arg(long)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub checkpoint: Option<PathBuf>,
+
+    /// Amount of the generations between the checkpoints
+    #[arg(long, visible_alias = "checkpoint-every", default_value_t = NonZeroU32::try_from(100).unwrap())]
+    pub checkpoint_interval: NonZeroU32,
+
+    /// Checkpoint file to continue the search from. Its config replaces the other arguments,
+    /// except for the checkpoint ones. Requires the `serde` feature
+    #[arg(long)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub resume: Option<PathBuf>,
+}
+
+impl Config {
+    /// Checks the invariants between the arguments, that can not be expressed by their types
+    pub fn validate(&self) -> anyhow::Result<()> {
+        ensure!(
+            self.generation_size.get() >= 2,
+            "Generation size ({}) is too small: at least 2 individuals are needed to choose the parents",
+            self.generation_size
+        );
+        ensure!(
+            !self.selection_strategy.uses_tournament()
+                || self.tournament_size <= self.generation_size,
+            "Tournament size ({}) must not exceed the generation size ({})",
+            self.tournament_size,
+            self.generation_size
+        );
+        ensure!(
+            self.elitism < self.generation_size.get(),
+            "Elitism ({}) must be less than the generation size ({})",
+            self.elitism,
+            self.generation_size
+        );
+        ensure!(
+            self.selection_strategy != SelectionStrategy::MuCommaLambda
+                || self.lambda >= self.generation_size,
+            "λ ({}) must not be less than μ, the generation size ({}), for the (μ,λ) selection",
+            self.lambda,
+            self.generation_size
+        );
+        ensure!(
+            self.sigma_factor.is_finite() && self.sigma_factor >= 0.0,
+            "Sigma factor ({}) must be a finite non-negative number",
+            self.sigma_factor
+        );
+        ensure!(
+            self.stagnation_epsilon.is_finite() && self.stagnation_epsilon >= 0.0,
+            "Stagnation epsilon ({}) must be a finite non-negative number",
+            self.stagnation_epsilon
+        );
+        ensure!(
+            self.queens.is_none_or(|queens| {
+                queens.get() <= self.board_width().min(self.board_height())
+            }),
+            "The amount of the queens ({}) must not exceed the board width ({}) nor height ({})",
+            self.pieces(),
+            self.board_width(),
+            self.board_height()
+        );
+        ensure!(
+            self.pieces() == self.board_height()
+                || matches!(
+                    self.crossover_strategy,
+                    CrossoverStrategy::SimilarGenes | CrossoverStrategy::Majority
+                ),
+            "Only the {} and {} crossovers support the non-square boards, not {}",
+            CrossoverStrategy::SimilarGenes,
+            CrossoverStrategy::Majority,
+            self.crossover_strategy
+        );
+        ensure!(
+            self.parents >= 2,
+            "A child must have at least 2 parents, not {}",
+            self.parents
+        );
+        ensure!(
+            self.parents == 2 || self.crossover_strategy == CrossoverStrategy::Majority,
+            "Only the {} crossover supports more than 2 parents, not {}",
+            CrossoverStrategy::Majority,
+            self.crossover_strategy
+        );
+        ensure!(
+            !self.collect_all
+                || self.solutions_wanted.is_some()
+                || self.max_generations.is_some()
+                || self.time_limit.is_some()
+                || self.stagnation_generations.is_some(),
+            "Collecting all the solutions requires a limit of the search: \
+             --solutions-wanted, --max-generations, --time-limit or --stagnation-generations"
+        );
+        if let Some(fixed) = &self.fixed {
+            fixed.check_bounds(self.pieces(), self.board_height())?;
+        }
+        self.blocked_cells()?;
+        #[cfg(not(feature = "serde"))]
+        ensure!(
+            self.checkpoint.is_none() && self.resume.is_none(),
+            "Checkpoints require the `serde` feature"
+        );
+        #[cfg(not(feature = "serde"))]
+        ensure!(
+            self.output_format != OutputFormat::Json,
+            "The JSON output requires the `serde` feature"
+        );
+        #[cfg(not(feature = "serde"))]
+        ensure!(
+            self.stats_json.is_none(),
+            "The JSON statistics require the `serde` feature"
+        );
+        Ok(())
+    }
+
+    /// Amount of the columns of the board
+    #[inline]
+    pub fn board_width(&self) -> u16 {
+        self.board_width.unwrap_or(self.board_size).get()
+    }
+
+    /// Amount of the pieces, one in each of the leftmost columns of the board
+    #[inline]
+    pub fn pieces(&self) -> u16 {
+        self.queens
+            .map_or_else(|| self.board_width(), NonZeroU16::get)
+    }
+
+    /// Amount of the rows of the board
+    #[inline]
+    pub fn board_height(&self) -> u16 {
+        self.board_height.unwrap_or(self.board_size).get()
+    }
+
+    /// Reads the cells of the `blocked_file`, checking that they are on the board. `None`, if there is no file
+    pub fn blocked_cells(&self) -> anyhow::Result<Option<BlockedCells>> {
+        let Some(path) = &self.blocked_file else {
+            return Ok(None);
+        };
+        let blocked = BlockedCells::load(path)?;
+        blocked.check_bounds(self.board_width(), self.board_height())?;
+        Ok(Some(blocked))
+    }
+
+    /// Amount of the distinct solutions, after which the search stops. Unlimited, if `None`
+    pub fn solutions_target(&self) -> Option<usize> {
+        match self.solutions_wanted {
+            Some(wanted) => Some(wanted.get()),
+            None if self.collect_all => None,
+            None => Some(1),
+        }
+    }
+
+    /// Creates the random number generator from the seed, or from the entropy if there is none
+    pub fn rng(&self) -> SolverRng {
+        match self.seed {
+            Some(seed) => SolverRng::seed_from_u64(seed),
+            None => SolverRng::from_entropy(),
+        }
+    }
+
+    /// Amount of the threads of the search, if it is limited: one for the deterministic search
+    pub fn threads(&self) -> Option<usize> {
+        if self.deterministic {
+            Some(1)
+        } else {
+            self.threads.map(NonZeroUsize::get)
+        }
+    }
+
+    /// Thread pool of the [`Config::threads`] to [`install`](rayon::ThreadPool::install) the search into,
+    /// or `None` for the global one
+    pub fn thread_pool(&self) -> anyhow::Result<Option<rayon::ThreadPool>> {
+        self.threads()
+            .map(|threads| rayon::ThreadPoolBuilder::new().num_threads(threads).build())
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    /// Whether the live progress is redrawn in place on the cleared screen
+    pub fn is_animated(&self) -> bool {
+        !self.no_color && !self.quiet && !self.verbose
+    }
+
+    /// Whether the live progress is drawn after the generation number `generation_count`
+    pub fn shows_progress(&self, generation_count: u32) -> bool {
+        self.is_animated() && generation_count.is_multiple_of(self.refresh_every.get())
+    }
+
+    #[inline]
+    pub fn selection_params(&self) -> SelectionParams {
+        SelectionParams {
+            tournament_size: self.tournament_size,
+            tournament_pressure: self.tournament_pressure,
+            rank_pressure: self.rank_pressure,
+            elitism: self.elitism,
+            crossover_strategy: self.crossover_strategy,
+            crossover_probability: self.crossover_probability,
+            parents: self.parents,
+            mutation_strategy: self.mutation_strategy,
+            gene_mutation_probability: self.gene_mutation_probability,
+            offspring_per_step: self.offspring_per_step,
+            fitness_scaling: self.fitness_scaling,
+            sigma_factor: self.sigma_factor,
+            sharing_radius: self.sharing_radius,
+            immigrant_rate: self.immigrant_rate,
+            lambda: self.lambda,
+            generation_gap: self.generation_gap,
+        }
+    }
+}
+
+impl Default for Config {
+    /// The defaults of all the command line arguments
+    fn default() -> Self {
+        Self::parse_from([env!("CARGO_PKG_NAME")])
+    }
+}
+
+/// Fluent construction of the [`Config`] for the library use, starting from the [`Config::default`]
+#[derive(Debug, Clone, Default)]
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+impl ConfigBuilder {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn board_size(mut self, board_size: NonZeroU16) -> Self {
+        self.config.board_size = board_size;
+        self
+    }
+
+    pub fn board_width(mut self, board_width: NonZeroU16) -> Self {
+        self.config.board_width = Some(board_width);
+        self
+    }
+
+    pub fn board_height(mut self, board_height: NonZeroU16) -> Self {
+        self.config.board_height = Some(board_height);
+        self
+    }
+
+    pub fn queens(mut self, queens: NonZeroU16) -> Self {
+        self.config.queens = Some(queens);
+        self
+    }
+
+    pub fn piece(mut self, piece: Piece) -> Self {
+        self.config.piece = piece;
+        self
+    }
+
+    pub fn blocked_file(mut self, blocked_file: PathBuf) -> Self {
+        self.config.blocked_file = Some(blocked_file);
+        self
+    }
+
+    pub fn fixed(mut self, fixed: FixedPieces) -> Self {
+        self.config.fixed = Some(fixed);
+        self
+    }
+
+    pub fn generation_size(mut self, generation_size: NonZeroUsize) -> Self {
+        self.config.generation_size = generation_size;
+        self
+    }
+
+    pub fn mutation_probability(mut self, mutation_probability: Probability) -> Self {
+        self.config.mutation_probability = mutation_probability;
+        self
+    }
+
+    pub fn mutation_strategy(mut self, mutation_strategy: MutationStrategy) -> Self {
+        self.config.mutation_strategy = mutation_strategy;
+        self
+    }
+
+    pub fn selection_strategy(mut self, selection_strategy: SelectionStrategy) -> Self {
+        self.config.selection_strategy = selection_strategy;
+        self
+    }
+
+    pub fn tournament_size(mut self, tournament_size: NonZeroUsize) -> Self {
+        self.config.tournament_size = tournament_size;
+        self
+    }
+
+    pub fn elitism(mut self, elitism: usize) -> Self {
+        self.config.elitism = elitism;
+        self
+    }
+
+    pub fn crossover_strategy(mut self, crossover_strategy: CrossoverStrategy) -> Self {
+        self.config.crossover_strategy = crossover_strategy;
+        self
+    }
+
+    pub fn crossover_probability(mut self, crossover_probability: Probability) -> Self {
+        self.config.crossover_probability = crossover_probability;
+        self
+    }
+
+    pub fn parents(mut self, parents: usize) -> Self {
+        self.config.parents = parents;
+        self
+    }
+
+    pub fn fitness_scaling(mut self, fitness_scaling: FitnessScaling) -> Self {
+        self.config.fitness_scaling = fitness_scaling;
+        self
+    }
+
+    pub fn sigma_factor(mut self, sigma_factor: f32) -> Self {
+        self.config.sigma_factor = sigma_factor;
+        self
+    }
+
+    pub fn lambda(mut self, lambda: NonZeroUsize) -> Self {
+        self.config.lambda = lambda;
+        self
+    }
+
+    pub fn generation_gap(mut self, generation_gap: GenerationGap) -> Self {
+        self.config.generation_gap = generation_gap;
+        self
+    }
+
+    pub fn solutions_wanted(mut self, solutions_wanted: NonZeroUsize) -> Self {
+        self.config.solutions_wanted = Some(solutions_wanted);
+        self
+    }
+
+    pub fn collect_all(mut self, collect_all: bool) -> Self {
+        self.config.collect_all = collect_all;
+        self
+    }
+
+    pub fn max_generations(mut self, max_generations: NonZeroU32) -> Self {
+        self.config.max_generations = Some(max_generations);
+        self
+    }
+
+    pub fn time_limit(mut self, time_limit: Duration) -> Self {
+        self.config.time_limit = Some(time_limit);
+        self
+    }
+
+    pub fn stagnation_generations(mut self, stagnation_generations: NonZeroU32) -> Self {
+        self.config.stagnation_generations = Some(stagnation_generations);
+        self
+    }
+
+    pub fn stagnation_epsilon(mut self, stagnation_epsilon: f32) -> Self {
+        self.config.stagnation_epsilon = stagnation_epsilon;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = Some(seed);
+        self
+    }
+
+    /// Checks the invariants of the [`Config::validate`]
+    pub fn build(self) -> anyhow::Result<Config> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
+/// Parses the duration from an integer amount and a unit: `ms`, `s`, `m` or `h`
+fn parse_duration(s: &str) -> anyhow::Result<Duration> {
+    let unit_start = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (amount, unit) = s.split_at(unit_start);
+    let amount: u64 = amount.parse()?;
+    Ok(match unit {
+        "ms" => Duration::from_millis(amount),
+        "s" => Duration::from_secs(amount),
+        "m" => Duration::from_secs(amount * 60),
+        "h" => Duration::from_secs(amount * 60 * 60),
+        _ => bail!("Unknown time unit {unit:?}. Expected one of: ms, s, m, h"),
+    })
 }
 
 impl fmt::Display for Probability {
@@ -38,12 +652,111 @@ impl FromStr for Probability {
     }
 }
 
+impl fmt::Display for AdaptiveMutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.min, self.max)
+    }
+}
+
+impl FromStr for AdaptiveMutation {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((min, max)) = s.split_once("..") else {
+            bail!("Expected the bounds like `0.05..0.5`, found {s:?}");
+        };
+        let (min, max): (Probability, Probability) = (min.trim().parse()?, max.trim().parse()?);
+        ensure!(
+            min <= max,
+            "The lower bound ({min}) must not exceed the upper one ({max})"
+        );
+        Ok(Self { min, max })
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = anyhow::Error;
+
+    /// Parses `linear:<from>..<to>@<generations>` or `exp:<initial>,<rate>`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((kind, parameters)) = s.split_once(':') else {
+            bail!("Expected the schedule like `linear:0.3..0.05@5000` or `exp:0.3,0.999`, found {s:?}");
+        };
+        match kind.trim() {
+            "linear" => {
+                let Some((range, generations)) = parameters.split_once('@') else {
+                    bail!("Expected the amount of the generations after `@`, found {parameters:?}");
+                };
+                let Some((from, to)) = range.split_once("..") else {
+                    bail!("Expected the range like `0.3..0.05`, found {range:?}");
+                };
+                Ok(Schedule::Linear {
+                    from: from.trim().parse()?,
+                    to: to.trim().parse()?,
+                    generations: generations.trim().parse()?,
+                })
+            }
+            "exp" => {
+                let Some((initial, rate)) = parameters.split_once(',') else {
+                    bail!("Expected the initial probability and the rate like `0.3,0.999`, found {parameters:?}");
+                };
+                let rate: f32 = rate.trim().parse()?;
+                ensure!(
+                    (0.0..=1.0).contains(&rate),
+                    "The rate of the decay must be within the range [0.0; 1.0], but was {rate}"
+                );
+                Ok(Schedule::Exponential {
+                    initial: initial.trim().parse()?,
+                    rate,
+                })
+            }
+            kind => bail!("Unknown schedule {kind:?}, expected `linear` or `exp`"),
+        }
+    }
+}
+
+impl fmt::Display for GenerationGap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for GenerationGap {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s.parse()?)
+    }
+}
+
+impl fmt::Display for RankPressure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for RankPressure {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s.parse()?)
+    }
+}
+
 impl fmt::Display for SelectionStrategy {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             SelectionStrategy::AdamAndEve => write!(f, "Adam and Eve"),
             SelectionStrategy::KillTheHalf => write!(f, "Kill the half"),
             SelectionStrategy::Tournament => write!(f, "Tournament"),
+            SelectionStrategy::Roulette => write!(f, "Roulette"),
+            SelectionStrategy::Rank => write!(f, "Rank"),
+            SelectionStrategy::Sus => write!(f, "Stochastic universal sampling"),
+            SelectionStrategy::SteadyState => write!(f, "Steady state"),
+            SelectionStrategy::Crowding => write!(f, "Crowding"),
+            SelectionStrategy::MuPlusLambda => write!(f, "Mu plus lambda"),
+            SelectionStrategy::MuCommaLambda => write!(f, "Mu comma lambda"),
+            SelectionStrategy::WeightedPairs => write!(f, "Weighted pairs"),
         }
     }
 }
@@ -51,10 +764,571 @@ impl fmt::Display for SelectionStrategy {
 impl ValueEnum for SelectionStrategy {
     fn value_variants<'a>() -> &'a [Self] {
         use SelectionStrategy::*;
-        &[AdamAndEve, KillTheHalf, Tournament]
+        &[
+            AdamAndEve,
+            KillTheHalf,
+            Tournament,
+            Roulette,
+            Rank,
+            Sus,
+            SteadyState,
+            Crowding,
+            MuPlusLambda,
+            MuCommaLambda,
+            WeightedPairs,
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(PossibleValue::new(self.to_string()))
+    }
+}
+
+impl fmt::Display for CrossoverStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CrossoverStrategy::SimilarGenes => write!(f, "similar-genes"),
+            CrossoverStrategy::Pmx => write!(f, "pmx"),
+            CrossoverStrategy::Ox => write!(f, "ox"),
+            CrossoverStrategy::Cx => write!(f, "cx"),
+            CrossoverStrategy::Erx => write!(f, "erx"),
+            CrossoverStrategy::Majority => write!(f, "majority"),
+        }
+    }
+}
+
+impl ValueEnum for CrossoverStrategy {
+    fn value_variants<'a>() -> &'a [Self] {
+        use CrossoverStrategy::*;
+        &[SimilarGenes, Pmx, Ox, Cx, Erx, Majority]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(PossibleValue::new(self.to_string()))
+    }
+}
+
+impl fmt::Display for MutationStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MutationStrategy::Swap => write!(f, "swap"),
+            MutationStrategy::Inversion => write!(f, "inversion"),
+            MutationStrategy::Insertion => write!(f, "insertion"),
+            MutationStrategy::Guided => write!(f, "guided"),
+            MutationStrategy::Scramble => write!(f, "scramble"),
+            MutationStrategy::Restart => write!(f, "restart"),
+        }
+    }
+}
+
+impl ValueEnum for MutationStrategy {
+    fn value_variants<'a>() -> &'a [Self] {
+        use MutationStrategy::*;
+        &[Swap, Inversion, Insertion, Guided, Scramble, Restart]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(PossibleValue::new(self.to_string()))
+    }
+}
+
+impl fmt::Display for FitnessScaling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FitnessScaling::None => write!(f, "none"),
+            FitnessScaling::Sigma => write!(f, "sigma"),
+        }
+    }
+}
+
+impl ValueEnum for FitnessScaling {
+    fn value_variants<'a>() -> &'a [Self] {
+        use FitnessScaling::*;
+        &[None, Sigma]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(PossibleValue::new(self.to_string()))
+    }
+}
+
+impl fmt::Display for Piece {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Piece::Queen => write!(f, "queen"),
+            Piece::Rook => write!(f, "rook"),
+            Piece::Bishop => write!(f, "bishop"),
+            Piece::Knight => write!(f, "knight"),
+            Piece::Amazon => write!(f, "amazon"),
+        }
+    }
+}
+
+impl ValueEnum for Piece {
+    fn value_variants<'a>() -> &'a [Self] {
+        use Piece::*;
+        &[Queen, Rook, Bishop, Knight, Amazon]
     }
 
     fn to_possible_value(&self) -> Option<PossibleValue> {
         Some(PossibleValue::new(self.to_string()))
     }
 }
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputFormat::Pretty => write!(f, "pretty"),
+            OutputFormat::Ascii => write!(f, "ascii"),
+            OutputFormat::Fen => write!(f, "fen"),
+            OutputFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+impl ValueEnum for OutputFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        use OutputFormat::*;
+        &[Pretty, Ascii, Fen, Json]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(PossibleValue::new(self.to_string()))
+    }
+}
+
+impl fmt::Display for Glyph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Glyph::Unicode => write!(f, "unicode"),
+            Glyph::Blocks => write!(f, "blocks"),
+            Glyph::Ascii => write!(f, "ascii"),
+        }
+    }
+}
+
+impl ValueEnum for Glyph {
+    fn value_variants<'a>() -> &'a [Self] {
+        use Glyph::*;
+        &[Unicode, Blocks, Ascii]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(PossibleValue::new(self.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{Parser, ValueEnum};
+
+    use std::{
+        num::{NonZeroU16, NonZeroU32, NonZeroUsize},
+        time::Duration,
+    };
+
+    use super::{
+        parse_duration, Config, ConfigBuilder, CrossoverStrategy, FitnessScaling, MutationStrategy,
+        Piece, Probability, Schedule, SelectionStrategy,
+    };
+
+    #[test]
+    fn test_tournament_size_exceeding_generation_size() {
+        for strategy in ["Tournament", "Steady state"] {
+            let config =
+                Config::parse_from(["", "-g", "10", "--tournament-size", "11", "-s", strategy]);
+            assert!(config.validate().is_err());
+            let config =
+                Config::parse_from(["", "-g", "10", "--tournament-size", "10", "-s", strategy]);
+            assert!(config.validate().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_tournament_size_ignored_without_tournaments() {
+        // the default tournament size of 3 exceeds the generation
+        let config = Config::parse_from(["", "-g", "2", "--elitism", "1"]);
+        assert!(config.validate().is_ok());
+        let config = Config::parse_from(["", "-g", "2", "--elitism", "1", "-s", "Kill the half"]);
+        assert!(config.validate().is_ok());
+        let config = Config::parse_from(["", "-g", "2", "--elitism", "1", "-s", "Tournament"]);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_elitism_not_less_than_generation_size() {
+        let config = Config::parse_from(["", "-g", "10", "--elitism", "10"]);
+        assert!(config.validate().is_err());
+        let config = Config::parse_from(["", "-g", "10", "--elitism", "9"]);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_crossover_alias() {
+        assert_eq!(
+            CrossoverStrategy::SimilarGenes,
+            Config::parse_from([""]).crossover_strategy
+        );
+        for strategy in CrossoverStrategy::value_variants() {
+            assert_eq!(
+                *strategy,
+                Config::parse_from(["", "--crossover", &strategy.to_string()]).crossover_strategy
+            );
+        }
+        assert_eq!(
+            CrossoverStrategy::Cx,
+            Config::parse_from(["", "--crossover", "cx"]).crossover_strategy
+        );
+        assert_eq!(
+            CrossoverStrategy::SimilarGenes,
+            Config::parse_from(["", "--crossover", "similar-genes"]).crossover_strategy
+        );
+    }
+
+    #[test]
+    fn test_piece() {
+        assert_eq!(Piece::Queen, Config::parse_from([""]).piece);
+        for piece in Piece::value_variants() {
+            assert_eq!(
+                *piece,
+                Config::parse_from(["", "--piece", &piece.to_string()]).piece
+            );
+        }
+        assert!(Config::try_parse_from(["", "--piece", "king"]).is_err());
+    }
+
+    #[test]
+    fn test_mutation_alias() {
+        assert_eq!(
+            MutationStrategy::Swap,
+            Config::parse_from([""]).mutation_strategy
+        );
+        for strategy in MutationStrategy::value_variants() {
+            assert_eq!(
+                *strategy,
+                Config::parse_from(["", "--mutation", &strategy.to_string()]).mutation_strategy
+            );
+        }
+        assert_eq!(
+            MutationStrategy::Swap,
+            Config::parse_from(["", "--mutation", "swap"]).mutation_strategy
+        );
+        assert_eq!(
+            MutationStrategy::Restart,
+            Config::parse_from(["", "--mutation", "restart"]).mutation_strategy
+        );
+    }
+
+    #[test]
+    fn test_lambda_less_than_mu() {
+        let config =
+            Config::parse_from(["", "-s", "Mu comma lambda", "--mu", "50", "--lambda", "49"]);
+        assert!(config.validate().is_err());
+        let config =
+            Config::parse_from(["", "-s", "Mu comma lambda", "--mu", "50", "--lambda", "50"]);
+        assert!(config.validate().is_ok());
+        let config =
+            Config::parse_from(["", "-s", "Mu plus lambda", "--mu", "50", "--lambda", "10"]);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_sigma_factor() {
+        for factor in ["-1", "NaN", "inf"] {
+            let config = Config::parse_from(["", &format!("--sigma-factor={factor}")]);
+            assert!(config.validate().is_err(), "{factor}");
+        }
+        for factor in ["0", "2.5"] {
+            let config = Config::parse_from(["", &format!("--sigma-factor={factor}")]);
+            assert!(config.validate().is_ok(), "{factor}");
+        }
+    }
+
+    #[test]
+    fn test_stagnation_epsilon() {
+        for epsilon in ["-0.1", "NaN", "inf"] {
+            let config = Config::parse_from(["", &format!("--stagnation-epsilon={epsilon}")]);
+            assert!(config.validate().is_err(), "{epsilon}");
+        }
+        for epsilon in ["0", "0.01"] {
+            let config = Config::parse_from(["", &format!("--stagnation-epsilon={epsilon}")]);
+            assert!(config.validate().is_ok(), "{epsilon}");
+        }
+    }
+
+    #[test]
+    fn test_parents() {
+        assert_eq!(2, Config::parse_from([""]).parents);
+        let config = Config::parse_from(["", "--parents", "1", "--crossover", "majority"]);
+        assert!(config.validate().is_err());
+        let config = Config::parse_from(["", "--parents", "3"]);
+        assert!(config.validate().is_err());
+        let config = Config::parse_from(["", "--parents", "3", "--crossover", "majority"]);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_shows_progress() {
+        let config = Config::parse_from([""]);
+        assert!((1..=5).all(|generation| config.shows_progress(generation)));
+        let config = Config::parse_from(["", "--refresh-every", "3"]);
+        assert_eq!(
+            vec![3, 6, 9],
+            (1..=10)
+                .filter(|generation| config.shows_progress(*generation))
+                .collect::<Vec<_>>()
+        );
+        for flag in ["--quiet", "--no-color", "--verbose"] {
+            let config = Config::parse_from(["", flag]);
+            assert!((1..=5).all(|generation| !config.shows_progress(generation)));
+        }
+    }
+
+    #[test]
+    fn test_quiet_conflicts_with_verbose() {
+        assert!(Config::try_parse_from(["", "--quiet", "--verbose"]).is_err());
+        assert!(!Config::parse_from([""]).verbose);
+        assert!(Config::parse_from(["", "-v"]).verbose);
+    }
+
+    #[test]
+    fn test_board_dimensions() {
+        let config = Config::parse_from(["", "-b", "6"]);
+        assert_eq!((6, 6), (config.board_width(), config.board_height()));
+        let config = Config::parse_from(["", "-b", "6", "--board-height", "9"]);
+        assert_eq!((6, 9), (config.board_width(), config.board_height()));
+        let config = Config::parse_from(["", "--board-width", "5", "--board-height", "7"]);
+        assert_eq!((5, 7), (config.board_width(), config.board_height()));
+        assert!(config.validate().is_ok());
+        let config = Config::parse_from(["", "--board-width", "5", "--crossover", "pmx"]);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_builder() {
+        let config = ConfigBuilder::new()
+            .board_size(NonZeroU16::new(10).unwrap())
+            .generation_size(NonZeroUsize::new(50).unwrap())
+            .mutation_probability(Probability(0.3))
+            .selection_strategy(SelectionStrategy::Tournament)
+            .seed(42)
+            .build()
+            .unwrap();
+        assert_eq!((10, 10), (config.board_width(), config.board_height()));
+        assert_eq!(50, config.generation_size.get());
+        assert_eq!(Probability(0.3), config.mutation_probability);
+        assert_eq!(SelectionStrategy::Tournament, config.selection_strategy);
+        assert_eq!(Some(42), config.seed);
+        // the rest are the defaults of the arguments
+        assert_eq!(Config::parse_from([""]).elitism, config.elitism);
+
+        let built = ConfigBuilder::new()
+            .generation_size(NonZeroUsize::new(1).unwrap())
+            .tournament_size(NonZeroUsize::new(1).unwrap())
+            .elitism(0)
+            .build();
+        assert!(built.is_err());
+        assert!(ConfigBuilder::new()
+            .board_width(NonZeroU16::new(5).unwrap())
+            .crossover_strategy(CrossoverStrategy::Pmx)
+            .build()
+            .is_err());
+
+        let config = ConfigBuilder::new()
+            .board_size(NonZeroU16::new(6).unwrap())
+            .queens(NonZeroU16::new(4).unwrap())
+            .fixed("0:3".parse().unwrap())
+            .crossover_strategy(CrossoverStrategy::Majority)
+            .parents(3)
+            .fitness_scaling(FitnessScaling::Sigma)
+            .sigma_factor(1.5)
+            .stagnation_generations(NonZeroU32::new(100).unwrap())
+            .stagnation_epsilon(0.01)
+            .collect_all(true)
+            .build()
+            .unwrap();
+        assert_eq!((4, 6), (config.pieces(), config.board_width()));
+        assert_eq!(3, config.parents);
+        assert_eq!(FitnessScaling::Sigma, config.fitness_scaling);
+        assert_eq!(1.5, config.sigma_factor);
+        assert!(config.collect_all);
+        assert_eq!(
+            "The amount of the queens (7) must not exceed the board width (6) nor height (6)",
+            ConfigBuilder::new()
+                .board_size(NonZeroU16::new(6).unwrap())
+                .queens(NonZeroU16::new(7).unwrap())
+                .build()
+                .unwrap_err()
+                .to_string()
+        );
+        assert!(ConfigBuilder::new()
+            .solutions_wanted(NonZeroUsize::new(2).unwrap())
+            .selection_strategy(SelectionStrategy::MuCommaLambda)
+            .lambda(NonZeroUsize::new(10).unwrap())
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_generation_size_too_small() {
+        let config =
+            Config::parse_from(["", "-g", "1", "--tournament-size", "1", "--elitism", "0"]);
+        assert_eq!(
+            "Generation size (1) is too small: at least 2 individuals are needed to choose the parents",
+            config.validate().unwrap_err().to_string()
+        );
+        let config =
+            Config::parse_from(["", "-g", "2", "--tournament-size", "2", "--elitism", "0"]);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_parse_mutation_schedule() {
+        let config = Config::parse_from(["", "--mutation-schedule", "linear:0.3..0.05@5000"]);
+        assert_eq!(
+            Some(Schedule::Linear {
+                from: Probability(0.3),
+                to: Probability(0.05),
+                generations: NonZeroU32::new(5000).unwrap(),
+            }),
+            config.mutation_schedule
+        );
+        assert_eq!(
+            Schedule::Exponential {
+                initial: Probability(0.3),
+                rate: 0.999,
+            },
+            "exp: 0.3, 0.999".parse().unwrap()
+        );
+        for invalid in [
+            "0.3..0.05@5000",
+            "linear:0.3..0.05",
+            "linear:0.3-0.05@5000",
+            "linear:0.3..1.05@5000",
+            "linear:0.3..0.05@0",
+            "exp:0.3",
+            "exp:0.3,1.5",
+            "exp:1.3,0.9",
+            "log:0.3,0.9",
+        ] {
+            assert!(invalid.parse::<Schedule>().is_err(), "{invalid}");
+        }
+        assert!(Config::try_parse_from([
+            "",
+            "--mutation-schedule",
+            "exp:0.3,0.999",
+            "--adaptive-mutation",
+            "0.1..0.5"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_threads() {
+        assert_eq!(None, Config::parse_from([""]).threads());
+        assert!(Config::parse_from([""]).thread_pool().unwrap().is_none());
+        assert_eq!(
+            Some(1),
+            Config::parse_from(["", "--deterministic"]).threads()
+        );
+
+        let config = Config::parse_from(["", "--threads", "3"]);
+        assert_eq!(Some(3), config.threads());
+        let pool = config.thread_pool().unwrap().unwrap();
+        assert_eq!(3, pool.install(rayon::current_num_threads));
+
+        assert!(Config::try_parse_from(["", "--threads", "0"]).is_err());
+        assert!(Config::try_parse_from(["", "--threads", "2", "--deterministic"]).is_err());
+    }
+
+    #[test]
+    fn test_fixed() {
+        let config = Config::parse_from(["", "-b", "8", "--fixed", "0:3, 5:1"]);
+        assert_eq!(
+            vec![(0, 3), (5, 1)],
+            config.fixed.as_ref().unwrap().iter().collect::<Vec<_>>()
+        );
+        assert!(config.validate().is_ok());
+        assert!(Config::parse_from(["", "-b", "8", "--fixed", "8:0"])
+            .validate()
+            .is_err());
+        assert!(Config::try_parse_from(["", "--fixed", "0:3,0:4"]).is_err());
+        assert!(Config::try_parse_from(["", "--fixed", "0:3,1:3"]).is_err());
+        assert!(Config::try_parse_from(["", "--fixed", "0-3"]).is_err());
+    }
+
+    #[test]
+    fn test_blocked_cells() {
+        assert_eq!(None, Config::parse_from([""]).blocked_cells().unwrap());
+
+        let path = std::env::temp_dir().join(format!(
+            "queen-placement-blocked-{}.txt",
+            std::process::id()
+        ));
+        std::fs::write(&path, "0 0\n7,7\n").unwrap();
+        let config = Config::parse_from(["", "-b", "8", "--blocked-file", path.to_str().unwrap()]);
+        assert!(config.validate().is_ok());
+        let blocked = config.blocked_cells().unwrap().unwrap();
+        assert!(blocked.contains(0, 0) && blocked.contains(7, 7));
+        let config = Config::parse_from(["", "-b", "7", "--blocked-file", path.to_str().unwrap()]);
+        assert!(config.validate().is_err());
+        std::fs::remove_file(&path).unwrap();
+        // the file is missing
+        let config = Config::parse_from(["", "-b", "8", "--blocked-file", path.to_str().unwrap()]);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_queens() {
+        let config = Config::parse_from(["", "-b", "8", "--queens", "5"]);
+        assert_eq!((8, 8), (config.board_width(), config.board_height()));
+        assert_eq!(5, config.pieces());
+        assert!(config.validate().is_ok());
+        let config = Config::parse_from(["", "-b", "8", "--queens", "9"]);
+        assert!(config.validate().is_err());
+        let config = Config::parse_from(["", "--queens", "5", "--board-width", "4"]);
+        assert!(config.validate().is_err());
+        // the fixed pieces are among the searched ones
+        let config = Config::parse_from(["", "-b", "8", "--queens", "5", "--fixed", "6:0"]);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_collect_all_requires_limit() {
+        let config = Config::parse_from(["", "--collect-all"]);
+        assert!(config.validate().is_err());
+        for limit in [
+            ["--solutions-wanted", "5"],
+            ["--max-generations", "100"],
+            ["--time-limit", "1s"],
+            ["--stagnation-generations", "10"],
+        ] {
+            let config = Config::parse_from(["", "--collect-all", limit[0], limit[1]]);
+            assert!(config.validate().is_ok(), "{limit:?}");
+        }
+    }
+
+    #[test]
+    fn test_solutions_target() {
+        assert_eq!(Some(1), Config::parse_from([""]).solutions_target());
+        assert_eq!(
+            None,
+            Config::parse_from(["", "--collect-all"]).solutions_target()
+        );
+        assert_eq!(
+            Some(3),
+            Config::parse_from(["", "--collect-all", "--solutions-wanted", "3"]).solutions_target()
+        );
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(Duration::from_millis(500), parse_duration("500ms").unwrap());
+        assert_eq!(Duration::from_secs(30), parse_duration("30s").unwrap());
+        assert_eq!(Duration::from_secs(120), parse_duration("2m").unwrap());
+        assert_eq!(Duration::from_secs(3600), parse_duration("1h").unwrap());
+        assert!(parse_duration("30").is_err());
+        assert!(parse_duration("s").is_err());
+        assert!(parse_duration("1.5s").is_err());
+    }
+}
@@ -0,0 +1,58 @@
+use std::{fs, path::Path};
+
+use anyhow::Context;
+
+use crate::{
+    selection::Generation,
+    snapshot::{generation_to_json, validate_generation},
+};
+
+/// Writes the generation as JSON into the file
+pub fn save_generation(path: &Path, generation: &Generation) -> anyhow::Result<()> {
+    fs::write(path, generation_to_json(generation)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Reads the generation, that was written by [`save_generation`].
+///
+/// The board dimensions are taken from the first chromosome, and all the others are checked against them
+pub fn load_generation(path: &Path) -> anyhow::Result<Generation> {
+    let json =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let generation: Generation = serde_json::from_str(&json)?;
+    if let Some(first) = generation.first() {
+        validate_generation(&generation, first.genes().len() as u16, first.height())?;
+    }
+    Ok(generation)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::chromosome::Chromosome;
+
+    use super::{load_generation, save_generation};
+
+    #[test]
+    fn test_save_and_load_generation() {
+        let generation = Chromosome::create_generation(8, 10, &mut StdRng::seed_from_u64(42));
+        let path = std::env::temp_dir().join(format!(
+            "queen-placement-generation-{}.json",
+            std::process::id()
+        ));
+        save_generation(&path, &generation).unwrap();
+        let loaded = load_generation(&path);
+        std::fs::write(
+            &path,
+            r#"[{"genes":[0,1],"height":2},{"genes":[0],"height":1}]"#,
+        )
+        .unwrap();
+        let mismatched = load_generation(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(generation, loaded.unwrap());
+        assert!(mismatched.is_err());
+    }
+}
@@ -1,4 +1,12 @@
 pub mod board;
 pub mod chromosome;
+pub mod config;
+#[cfg(feature = "serde")]
+pub mod io;
+pub mod rng;
+pub mod scaling;
 pub mod selection;
-pub mod config;
\ No newline at end of file
+#[cfg(feature = "serde")]
+pub mod snapshot;
+pub mod solver;
+pub mod stats;
@@ -0,0 +1,13 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+
+/// Generator, that drives the evolutionary search.
+///
+/// Same algorithm as [`StdRng`], but its state can be saved into a checkpoint
+pub type SolverRng = ChaCha12Rng;
+
+/// Creates `n` independent generators seeded from the `rng`,
+/// so that the parallel tasks stay reproducible regardless of the scheduling
+pub fn fork<R: Rng + ?Sized>(rng: &mut R, n: usize) -> Vec<StdRng> {
+    (0..n).map(|_| StdRng::seed_from_u64(rng.gen())).collect()
+}
@@ -0,0 +1,109 @@
+use rayon::prelude::{
+    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator,
+};
+
+use crate::selection::Generation;
+
+/// Transformation of the fitness values before the fitness-proportional selection
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FitnessScaling {
+    /// Raw fitness
+    None,
+    /// `max(f - (mean - c * stddev), floor)`
+    Sigma,
+}
+
+/// The least scaled fitness, so that everyone keeps a chance to be selected
+pub const SIGMA_FLOOR: f32 = 1e-3;
+
+pub fn scale(fitness: Vec<f32>, scaling: FitnessScaling, sigma_factor: f32) -> Vec<f32> {
+    match scaling {
+        FitnessScaling::None => fitness,
+        FitnessScaling::Sigma => sigma_scaled(fitness, sigma_factor),
+    }
+}
+
+fn sigma_scaled(fitness: Vec<f32>, c: f32) -> Vec<f32> {
+    if fitness.is_empty() {
+        return fitness;
+    }
+
+    let n = fitness.len() as f32;
+    let mean = fitness.par_iter().sum::<f32>() / n;
+    let stddev = (fitness.par_iter().map(|f| (f - mean).powi(2)).sum::<f32>() / n).sqrt();
+    let baseline = mean - c * stddev;
+
+    fitness
+        .into_par_iter()
+        .map(|f| (f - baseline).max(SIGMA_FLOOR))
+        .collect()
+}
+
+/// Divides the fitness of every individual by the amount of the individuals
+/// (including itself), that are closer than `sharing_radius` to it by the Hamming distance.
+/// Zero `sharing_radius` disables the sharing
+pub fn share(fitness: Vec<f32>, generation: &Generation, sharing_radius: usize) -> Vec<f32> {
+    if sharing_radius == 0 {
+        return fitness;
+    }
+
+    fitness
+        .into_par_iter()
+        .zip(generation.par_iter())
+        .map(|(f, ch1)| {
+            let niche_count = generation
+                .par_iter()
+                .filter(|ch2| ch1.hamming_distance(ch2) < sharing_radius)
+                .count();
+            f / niche_count as f32
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::chromosome::Chromosome;
+
+    use super::{scale, share, FitnessScaling, SIGMA_FLOOR};
+
+    #[test]
+    fn test_fitness_sharing() {
+        let generation = vec![
+            Chromosome::from(vec![0, 1, 2, 3]),
+            Chromosome::from(vec![0, 1, 2, 3]),
+            Chromosome::from(vec![3, 2, 1, 0]),
+        ];
+        let shared = share(vec![0.5; 3], &generation, 1);
+        assert_eq!(vec![0.25, 0.25, 0.5], shared);
+
+        let shared = share(vec![0.5; 3], &generation, 0);
+        assert_eq!(vec![0.5; 3], shared);
+    }
+
+    #[test]
+    fn test_sigma_scaling() {
+        // mean = 0.5, stddev = 0.25
+        let scaled = scale(vec![0.25, 0.75], FitnessScaling::Sigma, 1.0);
+        assert_eq!(vec![0.0f32.max(SIGMA_FLOOR), 0.5], scaled);
+
+        let scaled = scale(vec![0.25, 0.75], FitnessScaling::Sigma, 2.0);
+        assert_eq!(vec![0.25, 0.75], scaled);
+    }
+
+    #[test]
+    fn test_sigma_scaling_equal_fitness() {
+        let scaled = scale(vec![0.5; 4], FitnessScaling::Sigma, 2.0);
+        assert_eq!(vec![SIGMA_FLOOR; 4], scaled);
+    }
+
+    #[test]
+    fn test_no_scaling() {
+        assert_eq!(
+            vec![0.25, 0.75],
+            scale(vec![0.25, 0.75], FitnessScaling::None, 2.0)
+        );
+    }
+}
@@ -1,72 +1,818 @@
-use std::collections::VecDeque;
+use std::{
+    fmt, iter, mem,
+    num::NonZeroUsize,
+    ops::{Range, RangeInclusive},
+};
 
 use anyhow::ensure;
-use rand::{distributions, prelude::Distribution, random, seq::SliceRandom};
+use rand::{distributions, prelude::Distribution, rngs::StdRng, seq::SliceRandom, Rng, RngCore};
 use rayon::{
     prelude::{
-        IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator,
-        IntoParallelRefMutIterator, ParallelIterator,
+        IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator,
     },
-    slice::ParallelSliceMut,
+    slice::{ParallelSlice, ParallelSliceMut},
 };
 
 use crate::{
-    board::Board,
-    chromosome::{Chromosome, Gene},
+    chromosome::{Chromosome, Gene, SEQUENTIAL_LEN},
+    rng,
+    scaling::{self, FitnessScaling},
 };
 
 pub type Generation = Vec<Chromosome>;
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SelectionStrategy {
     AdamAndEve,
     KillTheHalf,
     Tournament,
+    Roulette,
+    Rank,
+    Sus,
+    SteadyState,
+    Crowding,
+    MuPlusLambda,
+    MuCommaLambda,
+    WeightedPairs,
 }
 
-pub fn new_generation(
-    selection_strategy: SelectionStrategy,
-    mut current_generation: Generation,
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MutationStrategy {
+    /// Swap two random genes
+    Swap,
+    /// Reverse a random segment of genes
+    Inversion,
+    /// Move a random gene to another position, shifting the genes in between
+    Insertion,
+    /// Swap a random beaten piece with a random other one
+    Guided,
+    /// Shuffle a random segment of genes
+    Scramble,
+    /// Shuffle all the genes, which replaces the child with a random one
+    Restart,
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CrossoverStrategy {
+    /// Keep the genes, that are the same in both parents, and shuffle the rest
+    SimilarGenes,
+    /// Partially-mapped crossover
+    Pmx,
+    /// Order crossover
+    Ox,
+    /// Cycle crossover
+    Cx,
+    /// Edge recombination crossover
+    Erx,
+    /// Keep the genes, that more than half of the parents agree on, and shuffle the rest.
+    /// The only operator, that crosses more than two parents
+    Majority,
+}
+
+/// Operators, that produce a child from the parents
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Reproduction {
+    crossover_strategy: CrossoverStrategy,
+    /// Probability to cross the parents. Otherwise the child is a clone of the fitter one
+    crossover_probability: Probability,
+    /// Amount of the parents of every child
+    parents: usize,
+    mutation_strategy: MutationStrategy,
     mutation_probability: Probability,
+    gene_mutation_probability: Probability,
+}
+
+/// Strategy-specific parameters of the selection
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SelectionParams {
+    /// Amount of individuals competing in one tournament
+    pub tournament_size: NonZeroUsize,
+    /// Probability that the best participant wins the tournament.
+    /// Otherwise one of the other participants wins at random
+    pub tournament_pressure: Probability,
+    /// Expected amount of offspring of the best individual in the rank selection
+    pub rank_pressure: RankPressure,
+    /// Amount of the best individuals that survive into the next generation unchanged.
+    /// They are cloned before the offspring are produced and never mutated,
+    /// so with at least one elite the best fitness never decreases from one generation to the next
+    pub elitism: usize,
+    /// Operator, that combines the parents into a child
+    pub crossover_strategy: CrossoverStrategy,
+    /// Probability to cross the parents. Otherwise the child is a clone of the fitter parent
+    pub crossover_probability: Probability,
+    /// Amount of the parents of every child. More than two are crossed only by [`CrossoverStrategy::Majority`],
+    /// and only the strategies, that sample the parents independently, choose more than two of them
+    pub parents: usize,
+    /// Operator, applied to the offspring on mutation
+    pub mutation_strategy: MutationStrategy,
+    /// Probability of every gene of the offspring to swap with a random other one,
+    /// independently of the mutation of the whole chromosome
+    pub gene_mutation_probability: Probability,
+    /// Transformation of the fitness before the fitness-proportional selection
+    pub fitness_scaling: FitnessScaling,
+    /// Amount of standard deviations below the mean fitness, that is scaled to zero by the sigma scaling
+    pub sigma_factor: f32,
+    /// Hamming distance, within which the individuals share their fitness. `0` disables the sharing
+    pub sharing_radius: usize,
+    /// Amount of the worst individuals replaced by the offspring in one step of the steady-state selection
+    pub offspring_per_step: NonZeroUsize,
+    /// Fraction of the non-elite individuals replaced by the random ones after each generation
+    pub immigrant_rate: Probability,
+    /// Amount of the offspring, produced by the (μ+λ) and (μ,λ) selection, where μ is the generation size.
+    /// The (μ,λ) selection requires λ ≥ μ
+    pub lambda: NonZeroUsize,
+    /// Fraction of the generation, that is replaced by the offspring. The fittest rest is carried over unchanged
+    pub generation_gap: GenerationGap,
+}
+
+impl Default for SelectionParams {
+    fn default() -> Self {
+        Self {
+            tournament_size: NonZeroUsize::new(3).unwrap(),
+            tournament_pressure: Probability(1.0),
+            rank_pressure: RankPressure(1.5),
+            elitism: 2,
+            crossover_strategy: CrossoverStrategy::SimilarGenes,
+            crossover_probability: Probability(0.9),
+            parents: 2,
+            mutation_strategy: MutationStrategy::Swap,
+            gene_mutation_probability: Probability(0.0),
+            offspring_per_step: NonZeroUsize::new(2).unwrap(),
+            fitness_scaling: FitnessScaling::None,
+            sigma_factor: 2.0,
+            sharing_radius: 0,
+            immigrant_rate: Probability(0.0),
+            lambda: NonZeroUsize::new(200).unwrap(),
+            generation_gap: GenerationGap(1.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionError {
+    /// The generation has not enough individuals for the selection
+    PopulationTooSmall {
+        population_size: usize,
+        min_population_size: usize,
+    },
+    /// The individuals of the generation have different amounts of genes, so they can not be crossed
+    MismatchedLengths { expected: usize, found: usize },
+}
+
+impl fmt::Display for SelectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectionError::PopulationTooSmall {
+                population_size,
+                min_population_size,
+            } => write!(
+                f,
+                "The selection requires at least {min_population_size} individuals, but the generation has {population_size}"
+            ),
+            SelectionError::MismatchedLengths { expected, found } => write!(
+                f,
+                "All the individuals must have {expected} genes, but one has {found}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SelectionError {}
+
+/// Context of one [`Selection::next_generation`] call
+pub struct SelectionCtx<'r> {
+    pub mutation_probability: Probability,
+    pub params: SelectionParams,
+    pub rng: &'r mut dyn RngCore,
+}
+
+/// Operator, that produces the next generation from the current one.
+///
+/// Implement it to plug a custom selection into the [`Solver`](crate::solver::Solver)
+pub trait Selection {
+    /// # Panics
+    ///
+    /// May panic, if the generation has less than [`Selection::min_population_size`] individuals
+    fn next_generation(&mut self, generation: Generation, ctx: &mut SelectionCtx<'_>)
+        -> Generation;
+
+    /// The smallest generation, that this selection can evolve
+    #[inline]
+    fn min_population_size(&self) -> usize {
+        1
+    }
+
+    /// Checks, that a generation of `population_size` individuals can be evolved by this selection
+    fn check_population_size(&self, population_size: usize) -> Result<(), SelectionError> {
+        let min_population_size = self.min_population_size();
+        if population_size < min_population_size {
+            return Err(SelectionError::PopulationTooSmall {
+                population_size,
+                min_population_size,
+            });
+        }
+        Ok(())
+    }
+
+    /// Amount of the new individuals, that are created (and evaluated) by one call to [`Selection::next_generation`].
+    ///
+    /// Assumes the whole generation is replaced, if not overridden
+    fn evaluations_per_generation(&self, population_size: usize, params: SelectionParams) -> usize {
+        let _ = params;
+        population_size
+    }
+}
+
+impl<S: Selection + ?Sized> Selection for Box<S> {
+    #[inline]
+    fn next_generation(
+        &mut self,
+        generation: Generation,
+        ctx: &mut SelectionCtx<'_>,
+    ) -> Generation {
+        (**self).next_generation(generation, ctx)
+    }
+
+    #[inline]
+    fn min_population_size(&self) -> usize {
+        (**self).min_population_size()
+    }
+
+    #[inline]
+    fn evaluations_per_generation(&self, population_size: usize, params: SelectionParams) -> usize {
+        (**self).evaluations_per_generation(population_size, params)
+    }
+}
+
+/// Replaces the generation with a child of the two fittest individuals
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AdamAndEve;
+
+/// Replaces the worse half of the generation with the children of the better one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KillTheHalf;
+
+/// Chooses each parent as the winner of a tournament
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tournament;
+
+/// Chooses the parents proportionally to their fitness
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Roulette;
+
+/// Chooses the parents by the linear weights of their ranks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Rank;
+
+/// Stochastic universal sampling of the parents proportionally to their fitness
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Sus;
+
+/// Replaces only a few of the worst individuals with the offspring
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SteadyState;
+
+/// Deterministic crowding: children replace the most similar parents, if they are not worse
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Crowding;
+
+/// Parents and λ offspring compete together for the places in the generation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MuPlusLambda;
+
+/// Only the λ offspring compete for the places in the generation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MuCommaLambda;
+
+/// Crosses a pair of distinct parents, chosen proportionally to their fitness, for every child
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WeightedPairs;
+
+/// Keeps the elites, lets `produce_offspring` replace the rest of the generation
+/// with exactly `offspring_count` individuals and injects the immigrants.
+///
+/// The elites are set aside before the offspring are produced, so the best fitness never decreases,
+/// if there is at least one of them
+fn generational_step(
+    mut current_generation: Generation,
+    ctx: &mut SelectionCtx<'_>,
+    produce_offspring: impl FnOnce(&mut Generation, Reproduction, usize, &mut dyn RngCore),
 ) -> Generation {
+    let params = ctx.params;
     let population_size = current_generation.len();
 
-    match selection_strategy {
-        SelectionStrategy::AdamAndEve => adam_and_eve_impl(
-            &mut current_generation,
-            mutation_probability,
-            population_size,
-        ),
-        SelectionStrategy::KillTheHalf => kill_the_half_impl(
-            &mut current_generation,
-            mutation_probability,
-            population_size,
-        ),
-        SelectionStrategy::Tournament => tournament_impl(
-            &mut current_generation,
-            mutation_probability,
-            population_size,
-        ),
+    let reproduction = Reproduction {
+        crossover_strategy: params.crossover_strategy,
+        crossover_probability: params.crossover_probability,
+        parents: params.parents,
+        mutation_strategy: params.mutation_strategy,
+        mutation_probability: ctx.mutation_probability,
+        gene_mutation_probability: params.gene_mutation_probability,
+    };
+
+    let elites = best_individuals(&current_generation, survivor_count(population_size, params));
+    let offspring_count = population_size - elites.len();
+    if offspring_count == 0 {
+        return elites;
     }
+    let best_fitness = elites.first().map(Chromosome::fitness);
+
+    produce_offspring(
+        &mut current_generation,
+        reproduction,
+        offspring_count,
+        ctx.rng,
+    );
+
+    let immigrants = immigrant_count(offspring_count, params.immigrant_rate);
+    inject_immigrants(&mut current_generation, immigrants, ctx.rng);
 
+    current_generation.extend(elites);
+
+    debug_assert!(
+        best_fitness.is_none_or(|best| current_generation.iter().any(|ch| ch.fitness() >= best)),
+        "The best fitness has decreased despite the elitism"
+    );
     current_generation
 }
 
-fn adam_and_eve_impl(
-    current_generation: &mut Generation,
+/// Evaluations of a [`generational_step`], that creates `offspring_evaluations(offspring_count)` children
+#[inline]
+fn generational_evaluations(
+    population_size: usize,
+    params: SelectionParams,
+    offspring_evaluations: impl FnOnce(usize) -> usize,
+) -> usize {
+    let offspring_count = population_size.saturating_sub(survivor_count(population_size, params));
+    let immigrants = immigrant_count(offspring_count, params.immigrant_rate);
+    immigrants + offspring_evaluations(offspring_count)
+}
+
+/// Amount of the fittest individuals, that are carried over unchanged:
+/// the elites, or more of them, if the generation gap is less than 1
+#[inline]
+fn survivor_count(population_size: usize, params: SelectionParams) -> usize {
+    let replaced = (population_size as f32 * params.generation_gap.0).ceil() as usize;
+    params.elitism.max(population_size.saturating_sub(replaced))
+}
+
+impl Selection for AdamAndEve {
+    fn next_generation(
+        &mut self,
+        generation: Generation,
+        ctx: &mut SelectionCtx<'_>,
+    ) -> Generation {
+        generational_step(
+            generation,
+            ctx,
+            |generation, reproduction, offspring_count, rng| {
+                adam_and_eve_impl(generation, reproduction, offspring_count, rng)
+            },
+        )
+    }
+
+    /// Two parents are needed
+    #[inline]
+    fn min_population_size(&self) -> usize {
+        2
+    }
+
+    #[inline]
+    fn evaluations_per_generation(&self, population_size: usize, params: SelectionParams) -> usize {
+        generational_evaluations(population_size, params, |offspring_count| offspring_count)
+    }
+}
+
+impl Selection for KillTheHalf {
+    fn next_generation(
+        &mut self,
+        generation: Generation,
+        ctx: &mut SelectionCtx<'_>,
+    ) -> Generation {
+        generational_step(
+            generation,
+            ctx,
+            |generation, reproduction, offspring_count, rng| {
+                kill_the_half_impl(generation, reproduction, offspring_count, rng)
+            },
+        )
+    }
+
+    /// Two parents are needed
+    #[inline]
+    fn min_population_size(&self) -> usize {
+        2
+    }
+
+    #[inline]
+    fn evaluations_per_generation(&self, population_size: usize, params: SelectionParams) -> usize {
+        generational_evaluations(population_size, params, |offspring_count| offspring_count)
+    }
+}
+
+impl Selection for Tournament {
+    fn next_generation(
+        &mut self,
+        generation: Generation,
+        ctx: &mut SelectionCtx<'_>,
+    ) -> Generation {
+        let params = ctx.params;
+        generational_step(
+            generation,
+            ctx,
+            |generation, reproduction, offspring_count, rng| {
+                tournament_impl(
+                    generation,
+                    reproduction,
+                    offspring_count,
+                    params.tournament_size,
+                    params.tournament_pressure,
+                    rng,
+                )
+            },
+        )
+    }
+
+    #[inline]
+    fn evaluations_per_generation(&self, population_size: usize, params: SelectionParams) -> usize {
+        generational_evaluations(population_size, params, |offspring_count| offspring_count)
+    }
+}
+
+impl Selection for Roulette {
+    fn next_generation(
+        &mut self,
+        generation: Generation,
+        ctx: &mut SelectionCtx<'_>,
+    ) -> Generation {
+        let params = ctx.params;
+        generational_step(
+            generation,
+            ctx,
+            |generation, reproduction, offspring_count, rng| {
+                roulette_impl(
+                    generation,
+                    reproduction,
+                    offspring_count,
+                    params.fitness_scaling,
+                    params.sigma_factor,
+                    params.sharing_radius,
+                    rng,
+                )
+            },
+        )
+    }
+
+    #[inline]
+    fn evaluations_per_generation(&self, population_size: usize, params: SelectionParams) -> usize {
+        generational_evaluations(population_size, params, |offspring_count| offspring_count)
+    }
+}
+
+impl Selection for Rank {
+    fn next_generation(
+        &mut self,
+        generation: Generation,
+        ctx: &mut SelectionCtx<'_>,
+    ) -> Generation {
+        let params = ctx.params;
+        generational_step(
+            generation,
+            ctx,
+            |generation, reproduction, offspring_count, rng| {
+                rank_impl(
+                    generation,
+                    reproduction,
+                    offspring_count,
+                    params.rank_pressure,
+                    params.sharing_radius,
+                    rng,
+                )
+            },
+        )
+    }
+
+    #[inline]
+    fn evaluations_per_generation(&self, population_size: usize, params: SelectionParams) -> usize {
+        generational_evaluations(population_size, params, |offspring_count| offspring_count)
+    }
+}
+
+impl Selection for Sus {
+    fn next_generation(
+        &mut self,
+        generation: Generation,
+        ctx: &mut SelectionCtx<'_>,
+    ) -> Generation {
+        let params = ctx.params;
+        generational_step(
+            generation,
+            ctx,
+            |generation, reproduction, offspring_count, rng| {
+                sus_impl(
+                    generation,
+                    reproduction,
+                    offspring_count,
+                    params.fitness_scaling,
+                    params.sigma_factor,
+                    params.sharing_radius,
+                    rng,
+                )
+            },
+        )
+    }
+
+    #[inline]
+    fn evaluations_per_generation(&self, population_size: usize, params: SelectionParams) -> usize {
+        generational_evaluations(population_size, params, |offspring_count| offspring_count)
+    }
+}
+
+impl Selection for SteadyState {
+    fn next_generation(
+        &mut self,
+        generation: Generation,
+        ctx: &mut SelectionCtx<'_>,
+    ) -> Generation {
+        let params = ctx.params;
+        generational_step(
+            generation,
+            ctx,
+            |generation, reproduction, offspring_count, rng| {
+                steady_state_impl(
+                    generation,
+                    reproduction,
+                    offspring_count,
+                    params.offspring_per_step,
+                    params.tournament_size,
+                    params.tournament_pressure,
+                    rng,
+                )
+            },
+        )
+    }
+
+    #[inline]
+    fn evaluations_per_generation(&self, population_size: usize, params: SelectionParams) -> usize {
+        generational_evaluations(population_size, params, |offspring_count| {
+            offspring_count.min(params.offspring_per_step.get())
+        })
+    }
+}
+
+impl Selection for Crowding {
+    fn next_generation(
+        &mut self,
+        generation: Generation,
+        ctx: &mut SelectionCtx<'_>,
+    ) -> Generation {
+        generational_step(
+            generation,
+            ctx,
+            |generation, reproduction, offspring_count, rng| {
+                crowding_impl(generation, reproduction, offspring_count, rng)
+            },
+        )
+    }
+
+    #[inline]
+    fn evaluations_per_generation(&self, population_size: usize, params: SelectionParams) -> usize {
+        generational_evaluations(population_size, params, |offspring_count| offspring_count)
+    }
+}
+
+impl Selection for MuPlusLambda {
+    fn next_generation(
+        &mut self,
+        generation: Generation,
+        ctx: &mut SelectionCtx<'_>,
+    ) -> Generation {
+        let lambda = ctx.params.lambda;
+        generational_step(
+            generation,
+            ctx,
+            |generation, reproduction, offspring_count, rng| {
+                mu_plus_lambda_impl(generation, reproduction, offspring_count, lambda, rng)
+            },
+        )
+    }
+
+    #[inline]
+    fn evaluations_per_generation(&self, population_size: usize, params: SelectionParams) -> usize {
+        generational_evaluations(population_size, params, |_| params.lambda.get())
+    }
+}
+
+impl Selection for MuCommaLambda {
+    fn next_generation(
+        &mut self,
+        generation: Generation,
+        ctx: &mut SelectionCtx<'_>,
+    ) -> Generation {
+        let lambda = ctx.params.lambda;
+        generational_step(
+            generation,
+            ctx,
+            |generation, reproduction, offspring_count, rng| {
+                mu_comma_lambda_impl(generation, reproduction, offspring_count, lambda, rng)
+            },
+        )
+    }
+
+    #[inline]
+    fn evaluations_per_generation(&self, population_size: usize, params: SelectionParams) -> usize {
+        generational_evaluations(population_size, params, |offspring_count| {
+            offspring_count.max(params.lambda.get())
+        })
+    }
+}
+
+impl Selection for WeightedPairs {
+    fn next_generation(
+        &mut self,
+        generation: Generation,
+        ctx: &mut SelectionCtx<'_>,
+    ) -> Generation {
+        generational_step(
+            generation,
+            ctx,
+            |generation, reproduction, offspring_count, rng| {
+                weighted_pairs_impl(generation, reproduction, offspring_count, rng)
+            },
+        )
+    }
+
+    #[inline]
+    fn min_population_size(&self) -> usize {
+        2
+    }
+
+    #[inline]
+    fn evaluations_per_generation(&self, population_size: usize, params: SelectionParams) -> usize {
+        generational_evaluations(population_size, params, |offspring_count| offspring_count)
+    }
+}
+
+impl SelectionStrategy {
+    /// Whether the parents are chosen by the tournaments of [`SelectionParams::tournament_size`]
+    #[inline]
+    pub fn uses_tournament(self) -> bool {
+        matches!(
+            self,
+            SelectionStrategy::Tournament | SelectionStrategy::SteadyState
+        )
+    }
+
+    /// The operator, that implements this strategy
+    pub fn selection(self) -> Box<dyn Selection> {
+        match self {
+            SelectionStrategy::AdamAndEve => Box::new(AdamAndEve),
+            SelectionStrategy::KillTheHalf => Box::new(KillTheHalf),
+            SelectionStrategy::Tournament => Box::new(Tournament),
+            SelectionStrategy::Roulette => Box::new(Roulette),
+            SelectionStrategy::Rank => Box::new(Rank),
+            SelectionStrategy::Sus => Box::new(Sus),
+            SelectionStrategy::SteadyState => Box::new(SteadyState),
+            SelectionStrategy::Crowding => Box::new(Crowding),
+            SelectionStrategy::MuPlusLambda => Box::new(MuPlusLambda),
+            SelectionStrategy::MuCommaLambda => Box::new(MuCommaLambda),
+            SelectionStrategy::WeightedPairs => Box::new(WeightedPairs),
+        }
+    }
+}
+
+pub fn new_generation<R: RngCore>(
+    selection_strategy: SelectionStrategy,
+    current_generation: Generation,
     mutation_probability: Probability,
+    params: SelectionParams,
+    rng: &mut R,
+) -> Result<Generation, SelectionError> {
+    let mut selection = selection_strategy.selection();
+    selection.check_population_size(current_generation.len())?;
+    check_lengths(&current_generation)?;
+    Ok(selection.next_generation(
+        current_generation,
+        &mut SelectionCtx {
+            mutation_probability,
+            params,
+            rng,
+        },
+    ))
+}
+
+/// Checks, that all the individuals of the `generation` have the same amount of genes,
+/// since the crossover of the different lengths would silently truncate the child
+pub fn check_lengths(generation: &Generation) -> Result<(), SelectionError> {
+    let Some(expected) = generation.first().map(|ch| ch.genes().len()) else {
+        return Ok(());
+    };
+    match generation
+        .par_iter()
+        .map(|ch| ch.genes().len())
+        .find_any(|found| *found != expected)
+    {
+        Some(found) => Err(SelectionError::MismatchedLengths { expected, found }),
+        None => Ok(()),
+    }
+}
+
+/// Amount of the new individuals, that are created (and evaluated) by one call to [`new_generation`]
+#[inline]
+pub fn evaluations_per_generation(
+    selection_strategy: SelectionStrategy,
     population_size: usize,
+    params: SelectionParams,
+) -> usize {
+    selection_strategy
+        .selection()
+        .evaluations_per_generation(population_size, params)
+}
+
+#[inline]
+fn immigrant_count(offspring_count: usize, immigrant_rate: Probability) -> usize {
+    ((offspring_count as f32 * immigrant_rate.0).round() as usize).min(offspring_count)
+}
+
+/// Replaces `n` distinct random individuals with the brand-new ones
+fn inject_immigrants<R: Rng + ?Sized>(current_generation: &mut Generation, n: usize, rng: &mut R) {
+    let Some(first) = current_generation.first() else {
+        return;
+    };
+    let (pieces, width, height) = (first.genes.len() as Gene, first.width(), first.height());
+    let (piece, blocked, fixed) = (first.piece(), first.blocked.clone(), first.fixed.clone());
+    let indices = rand::seq::index::sample(rng, current_generation.len(), n);
+    for i in indices {
+        current_generation[i] = Chromosome::new_rectangular(pieces, height, rng)
+            .with_width(width)
+            .with_piece(piece)
+            .with_blocked(blocked.clone())
+            .with_fixed(fixed.clone());
+    }
+}
+
+/// Clones the `n` fittest individuals, sorted from the best to the worst.
+/// If `n` is not less than the size of the generation, the whole generation is cloned
+fn best_individuals(current_generation: &Generation, n: usize) -> Generation {
+    let mut keyed: Vec<(OrdF32, usize)> = current_generation
+        .par_iter()
+        .enumerate()
+        .map(|(i, ch)| (OrdF32(-ch.fitness()), i))
+        .collect();
+
+    if n == 0 {
+        return Vec::new();
+    }
+    if n < keyed.len() {
+        keyed.select_nth_unstable(n - 1);
+        keyed.truncate(n);
+    }
+    keyed.par_sort_unstable();
+
+    keyed
+        .into_par_iter()
+        .map(|(_, i)| current_generation[i].clone())
+        .collect()
+}
+
+/// Replaces the generation with the children of its two fittest individuals.
+/// The parents themselves survive only as the elites of the [`generational_step`]
+fn adam_and_eve_impl<R: Rng + ?Sized>(
+    current_generation: &mut Generation,
+    reproduction: Reproduction,
+    offspring_count: usize,
+    rng: &mut R,
 ) {
     let parent1 = take_max(current_generation);
     let parent2 = take_max(current_generation);
 
-    (0..population_size - 2)
-        .into_par_iter()
-        .map(|_| crossover(&parent1, &parent2, mutation_probability))
-        .collect_into_vec(current_generation); // reuse of already allocated memory
+    *current_generation = breed(rng, offspring_count, parent1.genes.len(), |mut rng| {
+        crossover(&parent1, &parent2, reproduction, &mut rng)
+    });
+}
+
+/// Makes `n` children, each with its own generator forked from the `rng`.
+/// The chromosomes of less than [`SEQUENTIAL_LEN`] genes are crossed sequentially
+fn breed<R, F>(rng: &mut R, n: usize, gene_count: usize, child: F) -> Generation
+where
+    R: Rng + ?Sized,
+    F: Fn(StdRng) -> Chromosome + Sync + Send,
+{
+    let rngs = rng::fork(rng, n);
+    if gene_count < SEQUENTIAL_LEN {
+        rngs.into_iter().map(child).collect()
+    } else {
+        rngs.into_par_iter().map(child).collect()
+    }
+}
 
-    current_generation.push(parent1);
-    current_generation.push(parent2);
+/// Amount of the genes of the individuals in the generation
+#[inline]
+fn gene_count(current_generation: &Generation) -> usize {
+    current_generation.first().map_or(0, |ch| ch.genes.len())
 }
 
 fn take_max(current_generation: &mut Vec<Chromosome>) -> Chromosome {
@@ -74,160 +820,2006 @@ fn take_max(current_generation: &mut Vec<Chromosome>) -> Chromosome {
         current_generation
             .par_iter()
             .enumerate()
-            .max_by(|(_, ch1), (_, ch2)| {
-                f32::partial_cmp(&Board::from(*ch1).fitness(), &Board::from(*ch2).fitness())
-                    .unwrap()
-            })
+            .max_by(|(_, ch1), (_, ch2)| f32::partial_cmp(&ch1.fitness(), &ch2.fitness()).unwrap())
             .unwrap()
             .0,
     )
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 #[repr(transparent)]
 struct OrdF32(f32);
 
 impl Eq for OrdF32 {}
 
+impl PartialOrd for OrdF32 {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Ord for OrdF32 {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         self.0.total_cmp(&other.0)
     }
 }
 
-fn kill_the_half_impl(
+/// Keeps the better half and fills the rest with the children of the two fittest individuals,
+/// that, as the elites of the [`generational_step`], are not mutated
+fn kill_the_half_impl<R: Rng + ?Sized>(
     current_generation: &mut Generation,
-    mutation_probability: Probability,
-    population_size: usize,
+    reproduction: Reproduction,
+    offspring_count: usize,
+    rng: &mut R,
 ) {
-    current_generation.par_sort_by_cached_key(|ch| OrdF32(-Board::from(ch).fitness()));
+    let population_size = current_generation.len();
+    current_generation.par_sort_by_cached_key(|ch| OrdF32(-ch.fitness()));
 
-    // p p 1 1 1 1 1 1 1 1 1 0 0 0 0 0 0 0 0 0 0 0 (22)
-    let parent1 = current_generation.swap_remove(0);
-    let parent2 = current_generation.swap_remove(1);
+    let parent1 = current_generation[0].clone();
+    let parent2 = current_generation.get(1).unwrap_or(&parent1).clone();
 
-    // 0 0 1 1 1 1 1 1 1 1 1 0 0 0 0 0 0 0 0 0 (20)
+    // The better half survives, but its best individuals are already kept as the elites
+    let elitism = population_size - offspring_count;
     current_generation.truncate(population_size / 2);
-    // 0 0 1 1 1 1 1 1 1 1 1
-    current_generation.swap_remove(0);
-    // 1 0 1 1 1 1 1 1 1 1
-    current_generation.swap_remove(1);
-    // 1 1 1 1 1 1 1 1 1
-
-    current_generation.append(
-        &mut (0..population_size - population_size / 2)
-            .into_par_iter()
-            .map(|_| crossover(&parent1, &parent2, mutation_probability))
-            .collect::<Vec<_>>(),
-    );
+    current_generation.drain(..elitism.min(current_generation.len()));
 
-    current_generation.push(parent1);
-    current_generation.push(parent2);
+    let children = offspring_count - current_generation.len();
+    current_generation.append(&mut breed(rng, children, parent1.genes.len(), |mut rng| {
+        crossover(&parent1, &parent2, reproduction, &mut rng)
+    }));
 }
 
-fn tournament_impl(
-    _current_generation: &mut Generation,
-    _mutation_probability: Probability,
-    _population_size: usize,
+fn tournament_impl<R: Rng + ?Sized>(
+    current_generation: &mut Generation,
+    reproduction: Reproduction,
+    offspring_count: usize,
+    tournament_size: NonZeroUsize,
+    tournament_pressure: Probability,
+    rng: &mut R,
 ) {
-    todo!()
-}
-
-fn crossover(
-    parent1: &Chromosome,
-    parent2: &Chromosome,
-    mutation_probability: Probability,
-) -> Chromosome {
-    let gene_digit_base = parent1.genes.len();
-    #[cfg(debug_assertions)]
-    {
-        assert_eq!(gene_digit_base, parent2.genes.len());
-    }
+    // a tournament can not have more participants than there are individuals
+    let tournament_size = tournament_size.get().min(current_generation.len());
 
-    // Mutation (in this case it is a new completely random [`Chromosome`])
-    if random::<Probability>() < mutation_probability {
-        return Chromosome::new(gene_digit_base as u16);
-    }
+    let offspring = breed(
+        rng,
+        offspring_count,
+        gene_count(current_generation),
+        |mut rng| {
+            let parents: Vec<&Chromosome> = (0..reproduction.parents)
+                .map(|_| {
+                    tournament_winner(
+                        current_generation,
+                        tournament_size,
+                        tournament_pressure,
+                        &mut rng,
+                    )
+                })
+                .collect();
+            crossover_many(&parents, reproduction, &mut rng)
+        },
+    );
 
-    let mut similar_genes: Vec<Option<Gene>> = Vec::with_capacity(gene_digit_base);
-    let gene_digit_base = gene_digit_base as u16;
+    *current_generation = offspring;
+}
 
-    parent1
-        .genes()
-        .par_iter()
-        .zip(parent2.genes())
-        .map(|(p1, p2)| (p1 == p2).then_some(*p1))
-        .collect_into_vec(&mut similar_genes);
-
-    let rest_of_genes: Vec<Gene> = {
-        let mut rest_of_genes: Vec<_> = (0..gene_digit_base)
-            .into_par_iter()
-            .filter(|g| !similar_genes.contains(&Some(*g)))
-            .collect();
-        rest_of_genes.shuffle(&mut rand::thread_rng());
-        rest_of_genes
-    };
+/// Replaces only the `offspring_per_step` worst individuals with the offspring
+/// of the tournament winners, keeping everyone else
+fn steady_state_impl<R: Rng + ?Sized>(
+    current_generation: &mut Generation,
+    reproduction: Reproduction,
+    offspring_count: usize,
+    offspring_per_step: NonZeroUsize,
+    tournament_size: NonZeroUsize,
+    tournament_pressure: Probability,
+    rng: &mut R,
+) {
+    let population_size = current_generation.len();
+    let tournament_size = tournament_size.get().min(population_size);
+    let offspring_per_step = offspring_per_step.get().min(offspring_count);
 
-    similar_genes
-        .par_iter_mut()
-        .filter(|g| g.is_none())
-        .collect::<VecDeque<_>>()
-        .into_par_iter()
-        .zip(rest_of_genes.into_par_iter())
-        .for_each(|(none, gene)| *none = Some(gene));
+    let mut offspring = breed(
+        rng,
+        offspring_per_step,
+        gene_count(current_generation),
+        |mut rng| {
+            let parents: Vec<&Chromosome> = (0..reproduction.parents)
+                .map(|_| {
+                    tournament_winner(
+                        current_generation,
+                        tournament_size,
+                        tournament_pressure,
+                        &mut rng,
+                    )
+                })
+                .collect();
+            crossover_many(&parents, reproduction, &mut rng)
+        },
+    );
 
-    similar_genes
-        .into_par_iter()
-        .map(Option::unwrap)
-        .collect::<Vec<Gene>>()
-        .into()
+    current_generation.par_sort_by_cached_key(|ch| OrdF32(-ch.fitness()));
+    current_generation.truncate(population_size - offspring_per_step);
+    current_generation.append(&mut offspring);
+    // the best ones are re-added as the elites
+    current_generation.drain(..population_size - offspring_count);
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
-#[repr(transparent)]
-pub struct Probability(pub(crate) f32);
+/// Deterministic crowding: random pairs of parents produce two children,
+/// and each child replaces its most similar parent, only if it is at least as fit
+fn crowding_impl<R: Rng + ?Sized>(
+    current_generation: &mut Generation,
+    reproduction: Reproduction,
+    offspring_count: usize,
+    rng: &mut R,
+) {
+    let population_size = current_generation.len();
+    current_generation.shuffle(rng);
 
-impl Probability {
-    pub fn new(p: f32) -> anyhow::Result<Self> {
-        ensure!(
-            (0.0..=1.0).contains(&p),
-            "Probability must be within the range [0.0; 1.0], but was {}",
-            p
-        );
-        Ok(Self(p))
-    }
-}
+    let rngs = rng::fork(rng, population_size / 2);
+    let replace = |(parents, mut rng): (&mut [Chromosome], StdRng)| {
+        let child1 = crossover(&parents[0], &parents[1], reproduction, &mut rng);
+        let child2 = crossover(&parents[1], &parents[0], reproduction, &mut rng);
 
-impl TryFrom<f32> for Probability {
-    type Error = anyhow::Error;
+        let straight = parents[0].hamming_distance(&child1) + parents[1].hamming_distance(&child2);
+        let crossed = parents[0].hamming_distance(&child2) + parents[1].hamming_distance(&child1);
+        let (child1, child2) = if straight <= crossed {
+            (child1, child2)
+        } else {
+            (child2, child1)
+        };
 
-    #[inline]
-    fn try_from(value: f32) -> anyhow::Result<Self> {
-        Self::new(value)
+        for (parent, child) in parents.iter_mut().zip([child1, child2]) {
+            if child.fitness() >= parent.fitness() {
+                *parent = child;
+            }
+        }
+    };
+    if gene_count(current_generation) < SEQUENTIAL_LEN {
+        current_generation
+            .chunks_exact_mut(2)
+            .zip(rngs)
+            .for_each(replace);
+    } else {
+        current_generation
+            .par_chunks_exact_mut(2)
+            .zip(rngs)
+            .for_each(replace);
     }
+
+    current_generation.par_sort_by_cached_key(|ch| OrdF32(-ch.fitness()));
+    // the best ones are re-added as the elites
+    current_generation.drain(..population_size - offspring_count);
 }
 
-impl Distribution<Probability> for distributions::Standard {
-    #[inline]
-    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Probability {
-        Probability(rng.gen())
-    }
+/// Crosses `n` pairs of the parents, chosen uniformly at random
+fn random_offspring<R: Rng + ?Sized>(
+    current_generation: &Generation,
+    reproduction: Reproduction,
+    n: usize,
+    rng: &mut R,
+) -> Generation {
+    breed(rng, n, gene_count(current_generation), |mut rng| {
+        let parents: Vec<&Chromosome> = (0..reproduction.parents)
+            .map(|_| current_generation.choose(&mut rng).unwrap())
+            .collect();
+        crossover_many(&parents, reproduction, &mut rng)
+    })
 }
 
-#[cfg(test)]
-mod tests {
-    use rand::random;
+/// Keeps the `offspring_count` fittest of the parents and `lambda` offspring
+fn mu_plus_lambda_impl<R: Rng + ?Sized>(
+    current_generation: &mut Generation,
+    reproduction: Reproduction,
+    offspring_count: usize,
+    lambda: NonZeroUsize,
+    rng: &mut R,
+) {
+    let population_size = current_generation.len();
+    let mut offspring = random_offspring(current_generation, reproduction, lambda.get(), rng);
 
-    use crate::chromosome::Chromosome;
+    current_generation.par_sort_by_cached_key(|ch| OrdF32(-ch.fitness()));
+    // the best ones are re-added as the elites
+    current_generation.drain(..population_size - offspring_count);
 
-    use super::crossover;
+    current_generation.append(&mut offspring);
+    current_generation.par_sort_by_cached_key(|ch| OrdF32(-ch.fitness()));
+    current_generation.truncate(offspring_count);
+}
 
-    #[test]
+/// Keeps the `offspring_count` fittest of the `lambda` offspring.
+/// Produces `offspring_count` offspring instead, if `lambda` is less than that
+fn mu_comma_lambda_impl<R: Rng + ?Sized>(
+    current_generation: &mut Generation,
+    reproduction: Reproduction,
+    offspring_count: usize,
+    lambda: NonZeroUsize,
+    rng: &mut R,
+) {
+    let lambda = lambda.get().max(offspring_count);
+    *current_generation = random_offspring(current_generation, reproduction, lambda, rng);
+    current_generation.par_sort_by_cached_key(|ch| OrdF32(-ch.fitness()));
+    current_generation.truncate(offspring_count);
+}
+
+/// Picks `tournament_size` distinct individuals at random and returns the fittest of them
+/// with probability `tournament_pressure`, or a random other participant otherwise
+fn tournament_winner<'g, R: Rng + ?Sized>(
+    current_generation: &'g Generation,
+    tournament_size: usize,
+    tournament_pressure: Probability,
+    rng: &mut R,
+) -> &'g Chromosome {
+    let mut participants: Vec<&Chromosome> = current_generation
+        .choose_multiple(rng, tournament_size)
+        .collect();
+
+    let best = participants
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, ch)| OrdF32(ch.fitness()))
+        .unwrap()
+        .0;
+    let best = participants.swap_remove(best);
+
+    if participants.is_empty() || rng.gen::<Probability>() < tournament_pressure {
+        best
+    } else {
+        participants.choose(rng).unwrap()
+    }
+}
+
+/// Fitness of every individual, as seen by the fitness-based selection
+fn selection_fitness(current_generation: &Generation, sharing_radius: usize) -> Vec<f32> {
+    let fitness = current_generation
+        .par_iter()
+        .map(|ch| ch.fitness())
+        .collect();
+    scaling::share(fitness, current_generation, sharing_radius)
+}
+
+fn roulette_impl<R: Rng + ?Sized>(
+    current_generation: &mut Generation,
+    reproduction: Reproduction,
+    offspring_count: usize,
+    fitness_scaling: FitnessScaling,
+    sigma_factor: f32,
+    sharing_radius: usize,
+    rng: &mut R,
+) {
+    let fitness = selection_fitness(current_generation, sharing_radius);
+    let wheel = RouletteWheel::new(&scaling::scale(fitness, fitness_scaling, sigma_factor));
+
+    let offspring = breed(
+        rng,
+        offspring_count,
+        gene_count(current_generation),
+        |mut rng| {
+            let parents: Vec<&Chromosome> = (0..reproduction.parents)
+                .map(|_| &current_generation[wheel.spin(&mut rng)])
+                .collect();
+            crossover_many(&parents, reproduction, &mut rng)
+        },
+    );
+
+    *current_generation = offspring;
+}
+
+fn weighted_pairs_impl<R: Rng + ?Sized>(
+    current_generation: &mut Generation,
+    reproduction: Reproduction,
+    offspring_count: usize,
+    rng: &mut R,
+) {
+    let fitness: Vec<f32> = current_generation
+        .par_iter()
+        .map(Chromosome::fitness)
+        .collect();
+    let wheel = RouletteWheel::new(&fitness);
+
+    let offspring = breed(
+        rng,
+        offspring_count,
+        gene_count(current_generation),
+        |mut rng| {
+            let (parent1, parent2) = wheel.spin_pair(&mut rng);
+            crossover(
+                &current_generation[parent1],
+                &current_generation[parent2],
+                reproduction,
+                &mut rng,
+            )
+        },
+    );
+
+    *current_generation = offspring;
+}
+
+fn rank_impl<R: Rng + ?Sized>(
+    current_generation: &mut Generation,
+    reproduction: Reproduction,
+    offspring_count: usize,
+    rank_pressure: RankPressure,
+    sharing_radius: usize,
+    rng: &mut R,
+) {
+    let fitness = selection_fitness(current_generation, sharing_radius);
+    // indices of the worst individuals first, so that the position is the rank
+    let mut ranked: Vec<usize> = (0..current_generation.len()).collect();
+    ranked.par_sort_by_cached_key(|i| OrdF32(fitness[*i]));
+
+    let wheel = RouletteWheel::new(&linear_rank_weights(ranked.len(), rank_pressure));
+
+    let offspring = breed(
+        rng,
+        offspring_count,
+        gene_count(current_generation),
+        |mut rng| {
+            let parents: Vec<&Chromosome> = (0..reproduction.parents)
+                .map(|_| &current_generation[ranked[wheel.spin(&mut rng)]])
+                .collect();
+            crossover_many(&parents, reproduction, &mut rng)
+        },
+    );
+
+    *current_generation = offspring;
+}
+
+/// Stochastic universal sampling
+fn sus_impl<R: Rng + ?Sized>(
+    current_generation: &mut Generation,
+    reproduction: Reproduction,
+    offspring_count: usize,
+    fitness_scaling: FitnessScaling,
+    sigma_factor: f32,
+    sharing_radius: usize,
+    rng: &mut R,
+) {
+    let fitness = selection_fitness(current_generation, sharing_radius);
+    let wheel = RouletteWheel::new(&scaling::scale(fitness, fitness_scaling, sigma_factor));
+
+    let mut mating_pool = wheel.spin_universal(offspring_count * reproduction.parents, rng);
+    mating_pool.shuffle(rng);
+
+    let rngs = rng::fork(rng, offspring_count);
+    let child = |(parents, mut rng): (&[usize], StdRng)| {
+        let parents: Vec<&Chromosome> = parents.iter().map(|i| &current_generation[*i]).collect();
+        crossover_many(&parents, reproduction, &mut rng)
+    };
+    let offspring: Generation = if gene_count(current_generation) < SEQUENTIAL_LEN {
+        mating_pool
+            .chunks_exact(reproduction.parents)
+            .zip(rngs)
+            .map(child)
+            .collect()
+    } else {
+        mating_pool
+            .par_chunks_exact(reproduction.parents)
+            .zip(rngs)
+            .map(child)
+            .collect()
+    };
+
+    *current_generation = offspring;
+}
+
+/// Weights of the individuals sorted from the worst to the best.
+///
+/// The worst gets `2 - s` and the best gets `s` expected offspring, the rest are interpolated linearly
+fn linear_rank_weights(n: usize, rank_pressure: RankPressure) -> Vec<f32> {
+    let s = rank_pressure.0;
+    (0..n)
+        .into_par_iter()
+        .map(|rank| {
+            if n == 1 {
+                1.0
+            } else {
+                (2.0 - s) + 2.0 * (s - 1.0) * rank as f32 / (n - 1) as f32
+            }
+        })
+        .collect()
+}
+
+/// Samples indices of the individuals with the probability proportional to their fitness
+#[derive(Debug, Clone)]
+struct RouletteWheel {
+    cumulative: Vec<f32>,
+}
+
+impl RouletteWheel {
+    fn new(fitness: &[f32]) -> Self {
+        let all_equal = fitness.par_iter().all(|f| *f == fitness[0]);
+        let total: f32 = fitness.iter().sum();
+
+        // uniform sampling, when fitness does not distinguish the individuals
+        let weights: Vec<f32> = if all_equal || !total.is_normal() {
+            vec![1.0; fitness.len()]
+        } else {
+            fitness.to_vec()
+        };
+
+        let cumulative = weights
+            .iter()
+            .scan(0.0, |acc, w| {
+                *acc += w;
+                Some(*acc)
+            })
+            .collect();
+        Self { cumulative }
+    }
+
+    #[inline]
+    fn total(&self) -> f32 {
+        *self.cumulative.last().unwrap()
+    }
+
+    fn spin<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+        let pointer = rng.gen_range(0.0..self.total());
+        self.cumulative
+            .partition_point(|c| *c <= pointer)
+            .min(self.cumulative.len() - 1)
+    }
+
+    /// Samples two distinct indices. Requires at least two individuals
+    fn spin_pair<R: Rng + ?Sized>(&self, rng: &mut R) -> (usize, usize) {
+        debug_assert!(self.cumulative.len() >= 2);
+        let first = self.spin(rng);
+        loop {
+            let second = self.spin(rng);
+            if second != first {
+                return (first, second);
+            }
+        }
+    }
+
+    /// Samples `n` indices at once using equally spaced pointers with a single random offset
+    fn spin_universal<R: Rng + ?Sized>(&self, n: usize, rng: &mut R) -> Vec<usize> {
+        let step = self.total() / n as f32;
+        let start = rng.gen_range(0.0..step);
+
+        let mut indices = Vec::with_capacity(n);
+        let mut i = 0;
+        for pointer in (0..n).map(|k| start + k as f32 * step) {
+            while i < self.cumulative.len() - 1 && self.cumulative[i] <= pointer {
+                i += 1;
+            }
+            indices.push(i);
+        }
+        indices
+    }
+}
+
+fn crossover<R: Rng + ?Sized>(
+    parent1: &Chromosome,
+    parent2: &Chromosome,
+    reproduction: Reproduction,
+    rng: &mut R,
+) -> Chromosome {
+    crossover_many(&[parent1, parent2], reproduction, rng)
+}
+
+/// Crosses all the `parents` with [`CrossoverStrategy::Majority`], or the first two of them otherwise
+fn crossover_many<R: Rng + ?Sized>(
+    parents: &[&Chromosome],
+    reproduction: Reproduction,
+    rng: &mut R,
+) -> Chromosome {
+    let (parent1, parent2) = (parents[0], parents[1]);
+    // the certain crossover does not consume the random numbers
+    let crosses = reproduction.crossover_probability.0 >= 1.0
+        || rng.gen::<Probability>() < reproduction.crossover_probability;
+    let mut child = if !crosses {
+        // the first of the fittest parents
+        parents
+            .iter()
+            .skip(1)
+            .fold(parent1, |fittest, parent| {
+                if parent.fitness() > fittest.fitness() {
+                    parent
+                } else {
+                    fittest
+                }
+            })
+            .clone()
+    } else {
+        match reproduction.crossover_strategy {
+            CrossoverStrategy::SimilarGenes => crossover_similar_genes(parent1, parent2, rng),
+            CrossoverStrategy::Pmx => crossover_pmx(parent1, parent2, rng),
+            CrossoverStrategy::Ox => crossover_ox(parent1, parent2, rng),
+            CrossoverStrategy::Cx => crossover_cx(parent1, parent2),
+            CrossoverStrategy::Erx => crossover_erx(parent1, parent2, rng),
+            CrossoverStrategy::Majority => {
+                let genes: Vec<&[Gene]> = parents.iter().map(|parent| parent.genes()).collect();
+                Chromosome::with_height(majority(&genes, parent1.height, rng), parent1.height)
+            }
+        }
+    };
+    child.empty_columns = parent1.empty_columns;
+    child.piece = parent1.piece;
+    child.blocked.clone_from(&parent1.blocked);
+    child.fixed.clone_from(&parent1.fixed);
+
+    mutate(
+        &mut child,
+        reproduction.mutation_strategy,
+        reproduction.mutation_probability,
+        rng,
+    );
+    // skipped when disabled, so that it does not consume the random numbers
+    if reproduction.gene_mutation_probability.0 > 0.0 {
+        child.mutate_genes(reproduction.gene_mutation_probability, rng);
+    }
+    child.pin();
+    child
+}
+
+fn crossover_similar_genes<R: Rng + ?Sized>(
+    parent1: &Chromosome,
+    parent2: &Chromosome,
+    rng: &mut R,
+) -> Chromosome {
+    debug_assert_eq!(parent1.genes.len(), parent2.genes.len());
+    // both parents are the majority of two
+    Chromosome::with_height(
+        majority(&[&parent1.genes, &parent2.genes], parent1.height, rng),
+        parent1.height,
+    )
+}
+
+/// Keeps the genes, that more than half of the `parents` have at the same position,
+/// and fills the rest of the positions with the remaining genes in random order.
+///
+/// The genes are drawn from the rows `0..height`, each one as many times,
+/// as it is used by [`Chromosome::new_rectangular`], so the permutations stay permutations
+fn majority<R: Rng + ?Sized>(parents: &[&[Gene]], height: Gene, rng: &mut R) -> Vec<Gene> {
+    let len = parents[0].len();
+    let rows = height as usize;
+    let pool_size = len.max(rows);
+    let mut available: Vec<usize> = (0..rows)
+        .map(|row| pool_size / rows + usize::from(row < pool_size % rows))
+        .collect();
+    let mut child: Vec<Option<Gene>> = (0..len)
+        .map(|i| {
+            let gene = parents.iter().map(|parent| parent[i]).find(|gene| {
+                2 * parents.iter().filter(|parent| parent[i] == *gene).count() > parents.len()
+            })?;
+            let available = available
+                .get_mut(gene as usize)
+                .filter(|count| **count > 0)?;
+            *available -= 1;
+            Some(gene)
+        })
+        .collect();
+
+    let mut rest_of_genes: Vec<Gene> = (0..height)
+        .flat_map(|gene| iter::repeat_n(gene, available[gene as usize]))
+        .collect();
+    rest_of_genes.shuffle(rng);
+    child
+        .iter_mut()
+        .filter(|gene| gene.is_none())
+        .zip(rest_of_genes)
+        .for_each(|(hole, gene)| *hole = Some(gene));
+
+    child.into_iter().map(Option::unwrap).collect()
+}
+
+/// Partially-mapped crossover (PMX) with random cut points
+fn crossover_pmx<R: Rng + ?Sized>(
+    parent1: &Chromosome,
+    parent2: &Chromosome,
+    rng: &mut R,
+) -> Chromosome {
+    let len = parent1.genes.len();
+    let (a, b) = (rng.gen_range(0..=len), rng.gen_range(0..=len));
+    pmx(&parent1.genes, &parent2.genes, a.min(b)..a.max(b)).into()
+}
+
+/// Copies the `segment` from `parent1`, and the rest of the genes from `parent2`,
+/// following the mapping between the segments of the parents, when the gene is already copied
+fn pmx(parent1: &[Gene], parent2: &[Gene], segment: Range<usize>) -> Vec<Gene> {
+    let len = parent1.len();
+    let mut position_in_parent1 = vec![0; len];
+    for (i, gene) in parent1.iter().enumerate() {
+        position_in_parent1[*gene as usize] = i;
+    }
+
+    let mut child = parent2.to_vec();
+    child[segment.clone()].copy_from_slice(&parent1[segment.clone()]);
+    for i in (0..segment.start).chain(segment.end..len) {
+        let mut gene = parent2[i];
+        while segment.contains(&position_in_parent1[gene as usize]) {
+            gene = parent2[position_in_parent1[gene as usize]];
+        }
+        child[i] = gene;
+    }
+    child
+}
+
+/// Order crossover (OX) with random cut points
+fn crossover_ox<R: Rng + ?Sized>(
+    parent1: &Chromosome,
+    parent2: &Chromosome,
+    rng: &mut R,
+) -> Chromosome {
+    let len = parent1.genes.len();
+    let (a, b) = (rng.gen_range(0..=len), rng.gen_range(0..=len));
+    ox(&parent1.genes, &parent2.genes, a.min(b)..a.max(b)).into()
+}
+
+/// Copies the `segment` from `parent1`, and fills the rest of the positions
+/// with the remaining genes in the order they appear in `parent2`
+fn ox(parent1: &[Gene], parent2: &[Gene], segment: Range<usize>) -> Vec<Gene> {
+    let len = parent1.len();
+    let mut copied = vec![false; len];
+    for gene in &parent1[segment.clone()] {
+        copied[*gene as usize] = true;
+    }
+
+    let mut rest = parent2.iter().filter(|gene| !copied[**gene as usize]);
+    (0..len)
+        .map(|i| {
+            if segment.contains(&i) {
+                parent1[i]
+            } else {
+                *rest.next().unwrap()
+            }
+        })
+        .collect()
+}
+
+/// Cycle crossover (CX): takes the genes of the odd cycles between the parents from `parent1`,
+/// and of the even ones from `parent2`, so every gene keeps its position from one of the parents
+fn crossover_cx(parent1: &Chromosome, parent2: &Chromosome) -> Chromosome {
+    cycles(&parent1.genes, &parent2.genes)
+        .into_iter()
+        .enumerate()
+        .map(|(i, cycle)| {
+            if cycle % 2 == 0 {
+                parent1.genes[i]
+            } else {
+                parent2.genes[i]
+            }
+        })
+        .collect::<Vec<Gene>>()
+        .into()
+}
+
+/// Index of the cycle between the parents for every position, counting from `0`
+fn cycles(parent1: &[Gene], parent2: &[Gene]) -> Vec<usize> {
+    let len = parent1.len();
+    let mut position_in_parent1 = vec![0; len];
+    for (i, gene) in parent1.iter().enumerate() {
+        position_in_parent1[*gene as usize] = i;
+    }
+
+    let mut cycles = vec![usize::MAX; len];
+    let mut cycle = 0;
+    for start in 0..len {
+        if cycles[start] != usize::MAX {
+            continue;
+        }
+        let mut i = start;
+        while cycles[i] == usize::MAX {
+            cycles[i] = cycle;
+            i = position_in_parent1[parent2[i] as usize];
+        }
+        cycle += 1;
+    }
+    cycles
+}
+
+/// Edge recombination crossover (ERX), that keeps the genes next to the ones,
+/// they are next to in any of the parents
+#[inline]
+fn crossover_erx<R: Rng + ?Sized>(
+    parent1: &Chromosome,
+    parent2: &Chromosome,
+    rng: &mut R,
+) -> Chromosome {
+    erx(&parent1.genes, &parent2.genes, rng).into()
+}
+
+/// Starts with the first gene of a random parent and every time moves to the neighbor of the last gene
+/// with the fewest remaining neighbors, or to a random unused gene, if the last one has no neighbors left
+fn erx<R: Rng + ?Sized>(parent1: &[Gene], parent2: &[Gene], rng: &mut R) -> Vec<Gene> {
+    let len = parent1.len();
+    if len == 0 {
+        return Vec::new();
+    }
+
+    let mut neighbors: Vec<Vec<Gene>> = vec![Vec::with_capacity(4); len];
+    for parent in [parent1, parent2] {
+        for (i, gene) in parent.iter().enumerate() {
+            let adjacent = i.checked_sub(1).into_iter().chain([i + 1]);
+            for neighbor in adjacent.filter_map(|j| parent.get(j)) {
+                let list = &mut neighbors[*gene as usize];
+                if !list.contains(neighbor) {
+                    list.push(*neighbor);
+                }
+            }
+        }
+    }
+
+    let mut used = vec![false; len];
+    let mut child = Vec::with_capacity(len);
+    let mut current = if rng.gen() { parent1[0] } else { parent2[0] };
+    loop {
+        child.push(current);
+        used[current as usize] = true;
+        if child.len() == len {
+            break child;
+        }
+
+        let remaining = mem::take(&mut neighbors[current as usize]);
+        for neighbor in &remaining {
+            neighbors[*neighbor as usize].retain(|gene| *gene != current);
+        }
+        let fewest = remaining
+            .iter()
+            .map(|gene| neighbors[*gene as usize].len())
+            .min();
+        let candidates: Vec<Gene> = match fewest {
+            Some(fewest) => remaining
+                .into_iter()
+                .filter(|gene| neighbors[*gene as usize].len() == fewest)
+                .collect(),
+            None => (0..len as Gene)
+                .filter(|gene| !used[*gene as usize])
+                .collect(),
+        };
+        current = *candidates.choose(rng).unwrap();
+    }
+}
+
+/// With probability `mutation_probability` applies the `mutation_strategy` to the `chromosome`
+fn mutate<R: Rng + ?Sized>(
+    chromosome: &mut Chromosome,
+    mutation_strategy: MutationStrategy,
+    mutation_probability: Probability,
+    rng: &mut R,
+) {
+    if chromosome.genes.len() < 2 || rng.gen::<Probability>() >= mutation_probability {
+        return;
+    }
+
+    match mutation_strategy {
+        MutationStrategy::Swap => chromosome.mutate_swap(rng),
+        MutationStrategy::Inversion => chromosome.mutate_invert(rng),
+        MutationStrategy::Insertion => chromosome.mutate_insert(rng),
+        MutationStrategy::Guided => chromosome.mutate_guided(rng),
+        MutationStrategy::Scramble => mutate_scramble(chromosome, rng),
+        MutationStrategy::Restart => chromosome.genes_mut().shuffle(rng),
+    }
+}
+
+/// Shuffles a random segment of at least two genes of the `chromosome`
+fn mutate_scramble<R: Rng + ?Sized>(chromosome: &mut Chromosome, rng: &mut R) {
+    let segment = random_segment(chromosome.genes.len(), rng);
+    chromosome.genes_mut()[segment].shuffle(rng);
+}
+
+/// Random range of at least two positions within `0..len`
+pub(crate) fn random_segment<R: Rng + ?Sized>(len: usize, rng: &mut R) -> RangeInclusive<usize> {
+    let positions = rand::seq::index::sample(rng, len, 2);
+    let (a, b) = (positions.index(0), positions.index(1));
+    a.min(b)..=a.max(b)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "f32")
+)]
+#[repr(transparent)]
+pub struct Probability(pub(crate) f32);
+
+impl Probability {
+    pub fn new(p: f32) -> anyhow::Result<Self> {
+        ensure!(
+            (0.0..=1.0).contains(&p),
+            "Probability must be within the range [0.0; 1.0], but was {}",
+            p
+        );
+        Ok(Self(p))
+    }
+}
+
+/// Selection pressure of the linear rank selection, within the range [1.0; 2.0]
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "f32")
+)]
+#[repr(transparent)]
+pub struct RankPressure(pub(crate) f32);
+
+impl RankPressure {
+    pub fn new(s: f32) -> anyhow::Result<Self> {
+        ensure!(
+            (1.0..=2.0).contains(&s),
+            "Rank pressure must be within the range [1.0; 2.0], but was {}",
+            s
+        );
+        Ok(Self(s))
+    }
+}
+
+impl TryFrom<f32> for RankPressure {
+    type Error = anyhow::Error;
+
+    #[inline]
+    fn try_from(value: f32) -> anyhow::Result<Self> {
+        Self::new(value)
+    }
+}
+
+/// Fraction of the generation, within the range (0.0; 1.0]
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "f32")
+)]
+#[repr(transparent)]
+pub struct GenerationGap(pub(crate) f32);
+
+impl GenerationGap {
+    pub fn new(g: f32) -> anyhow::Result<Self> {
+        ensure!(
+            g > 0.0 && g <= 1.0,
+            "Generation gap must be within the range (0.0; 1.0], but was {}",
+            g
+        );
+        Ok(Self(g))
+    }
+}
+
+impl TryFrom<f32> for GenerationGap {
+    type Error = anyhow::Error;
+
+    #[inline]
+    fn try_from(value: f32) -> anyhow::Result<Self> {
+        Self::new(value)
+    }
+}
+
+impl TryFrom<f32> for Probability {
+    type Error = anyhow::Error;
+
+    #[inline]
+    fn try_from(value: f32) -> anyhow::Result<Self> {
+        Self::new(value)
+    }
+}
+
+impl Distribution<Probability> for distributions::Standard {
+    #[inline]
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Probability {
+        Probability(rng.gen())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, num::NonZeroUsize, sync::Arc};
+
+    use clap::ValueEnum;
+    use rand::{random, rngs::StdRng, thread_rng, Rng, SeedableRng};
+
+    use crate::{
+        board::{Board, FixedPieces},
+        chromosome::{is_permutation, Chromosome, Gene},
+    };
+
+    use super::{
+        best_individuals, crossover, crossover_cx, crossover_erx, crossover_many, crossover_ox,
+        crossover_pmx, crossover_similar_genes, cycles, erx, kill_the_half_impl,
+        linear_rank_weights, majority, mutate, mutate_scramble, new_generation, ox, pmx,
+        CrossoverStrategy, Generation, GenerationGap, MutationStrategy, Probability, RankPressure,
+        Reproduction, RouletteWheel, SelectionError, SelectionParams, SelectionStrategy,
+    };
+
+    #[test]
+    fn test_tournament_size_is_clamped() {
+        let generation = Chromosome::create_generation(5, 4, &mut thread_rng());
+        let new_generation = new_generation(
+            SelectionStrategy::Tournament,
+            generation,
+            random(),
+            SelectionParams {
+                tournament_size: NonZeroUsize::new(10).unwrap(),
+                ..Default::default()
+            },
+            &mut thread_rng(),
+        )
+        .unwrap();
+        assert_eq!(4, new_generation.len());
+    }
+
+    #[test]
+    fn test_tournament_small_size() {
+        let generation = Chromosome::create_generation(8, 20, &mut thread_rng());
+        let new_generation = new_generation(
+            SelectionStrategy::Tournament,
+            generation,
+            random(),
+            SelectionParams {
+                tournament_size: NonZeroUsize::new(1).unwrap(),
+                ..Default::default()
+            },
+            &mut thread_rng(),
+        )
+        .unwrap();
+        assert_eq!(20, new_generation.len());
+    }
+
+    #[test]
+    fn test_tournament_large_size() {
+        // when everyone participates, the best individual always wins,
+        // so without mutation every child is a copy of it
+        let solution = Chromosome::from(vec![1, 3, 0, 2]);
+        let mut generation = vec![Chromosome::from(vec![0, 1, 2, 3]); 19];
+        generation.push(solution.clone());
+        let new_generation = new_generation(
+            SelectionStrategy::Tournament,
+            generation,
+            Probability(0.0),
+            SelectionParams {
+                tournament_size: NonZeroUsize::new(20).unwrap(),
+                elitism: 0,
+                ..Default::default()
+            },
+            &mut thread_rng(),
+        )
+        .unwrap();
+        assert_eq!(20, new_generation.len());
+        assert!(new_generation.iter().all(|ch| ch == &solution));
+    }
+
+    #[test]
+    fn test_tournament_no_pressure() {
+        // when the best participant never wins, the only solution can not be a parent,
+        // so without mutation no child is a copy of it
+        let solution = Chromosome::from(vec![1, 3, 0, 2]);
+        let mut generation = vec![Chromosome::from(vec![0, 1, 2, 3]); 19];
+        generation.push(solution.clone());
+        let new_generation = new_generation(
+            SelectionStrategy::Tournament,
+            generation,
+            Probability(0.0),
+            SelectionParams {
+                tournament_size: NonZeroUsize::new(20).unwrap(),
+                tournament_pressure: Probability(0.0),
+                elitism: 0,
+                ..Default::default()
+            },
+            &mut thread_rng(),
+        )
+        .unwrap();
+        assert_eq!(20, new_generation.len());
+        assert!(new_generation.iter().all(|ch| ch != &solution));
+    }
+
+    #[test]
+    fn test_roulette_prefers_fitter() {
+        let wheel = RouletteWheel::new(&[0.1, 0.2, 0.7]);
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut hits = [0usize; 3];
+        for _ in 0..10_000 {
+            hits[wheel.spin(&mut rng)] += 1;
+        }
+        assert!(hits[0] < hits[1]);
+        assert!(hits[1] < hits[2]);
+    }
+
+    #[test]
+    fn test_roulette_equal_fitness() {
+        let wheel = RouletteWheel::new(&[0.5; 4]);
+        assert_eq!(4.0, wheel.total());
+        let wheel = RouletteWheel::new(&[0.0; 4]);
+        assert_eq!(4.0, wheel.total());
+    }
+
+    #[test]
+    fn test_roulette() {
+        let generation = Chromosome::create_generation(8, 20, &mut thread_rng());
+        let new_generation = new_generation(
+            SelectionStrategy::Roulette,
+            generation,
+            random(),
+            SelectionParams::default(),
+            &mut thread_rng(),
+        )
+        .unwrap();
+        assert_eq!(20, new_generation.len());
+    }
+
+    #[test]
+    fn test_rank() {
+        let generation = Chromosome::create_generation(8, 20, &mut thread_rng());
+        let new_generation = new_generation(
+            SelectionStrategy::Rank,
+            generation,
+            random(),
+            SelectionParams::default(),
+            &mut thread_rng(),
+        )
+        .unwrap();
+        assert_eq!(20, new_generation.len());
+    }
+
+    #[test]
+    fn test_sus_expected_copies() {
+        let fitness = [0.1, 0.2, 0.3, 0.4];
+        let wheel = RouletteWheel::new(&fitness);
+        let mut rng = StdRng::seed_from_u64(42);
+        for n in [10, 25, 100] {
+            let indices = wheel.spin_universal(n, &mut rng);
+            assert_eq!(n, indices.len());
+            let copies = indices.iter().filter(|i| **i == 3).count() as f32;
+            let expected = n as f32 * fitness[3];
+            assert!(
+                (copies - expected).abs() <= 1.0,
+                "n = {n}, copies = {copies}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_rank_pressure() {
+        for s in [1.0, 1.5, 2.0] {
+            let n = 101;
+            let wheel = RouletteWheel::new(&linear_rank_weights(n, RankPressure(s)));
+            let mut rng = StdRng::seed_from_u64(42);
+            let mut hits = vec![0usize; n];
+            for _ in 0..200_000 {
+                hits[wheel.spin(&mut rng)] += 1;
+            }
+            let ratio = hits[n - 1] as f32 / hits[n / 2] as f32;
+            assert!((ratio - s).abs() < 0.15 * s, "s = {s}, ratio = {ratio}");
+        }
+    }
+
+    #[test]
+    fn test_elitism_two() {
+        let solution1 = Chromosome::from(vec![1, 3, 0, 2]);
+        let solution2 = Chromosome::from(vec![2, 0, 3, 1]);
+        let mut generation = vec![Chromosome::from(vec![0, 1, 2, 3]); 18];
+        generation.push(solution1.clone());
+        generation.push(solution2.clone());
+        for selection_strategy in SelectionStrategy::value_variants() {
+            let new_generation = new_generation(
+                *selection_strategy,
+                generation.clone(),
+                Probability(1.0),
+                SelectionParams {
+                    elitism: 2,
+                    ..Default::default()
+                },
+                &mut thread_rng(),
+            )
+            .unwrap();
+            assert_eq!(20, new_generation.len());
+            assert!(new_generation.contains(&solution1));
+            assert!(new_generation.contains(&solution2));
+        }
+    }
+
+    #[test]
+    fn test_elites_survive_every_strategy() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let generation = Chromosome::create_generation(8, 30, &mut rng);
+        let elites = best_individuals(&generation, 5);
+        for selection_strategy in SelectionStrategy::value_variants() {
+            let new_generation = new_generation(
+                *selection_strategy,
+                generation.clone(),
+                Probability(1.0),
+                SelectionParams {
+                    elitism: 5,
+                    ..Default::default()
+                },
+                &mut rng,
+            )
+            .unwrap();
+            assert_eq!(30, new_generation.len());
+            for elite in &elites {
+                assert!(
+                    new_generation.contains(elite),
+                    "{selection_strategy} lost {elite:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_best_fitness_never_decreases_with_elitism() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for selection_strategy in SelectionStrategy::value_variants() {
+            let mut generation = Chromosome::create_generation(8, 20, &mut rng);
+            let mut best = best_individuals(&generation, 1)[0].fitness();
+            for _ in 0..30 {
+                generation = new_generation(
+                    *selection_strategy,
+                    generation,
+                    Probability(1.0),
+                    SelectionParams {
+                        elitism: 1,
+                        immigrant_rate: Probability(0.5),
+                        ..Default::default()
+                    },
+                    &mut rng,
+                )
+                .unwrap();
+                let next_best = best_individuals(&generation, 1)[0].fitness();
+                assert!(
+                    next_best >= best,
+                    "{selection_strategy}: {next_best} < {best}"
+                );
+                best = next_best;
+            }
+        }
+    }
+
+    #[test]
+    fn test_perfect_parent_survives_restart() {
+        let solution = Chromosome::from(vec![1, 5, 0, 6, 3, 7, 2, 4]);
+        assert_eq!(1.0, solution.fitness());
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut generation = Chromosome::create_generation(8, 19, &mut rng);
+        generation.push(solution.clone());
+        for selection_strategy in [
+            SelectionStrategy::AdamAndEve,
+            SelectionStrategy::KillTheHalf,
+        ] {
+            let new_generation = new_generation(
+                selection_strategy,
+                generation.clone(),
+                Probability(1.0),
+                SelectionParams {
+                    elitism: 1,
+                    crossover_probability: Probability(0.0),
+                    mutation_strategy: MutationStrategy::Restart,
+                    ..Default::default()
+                },
+                &mut rng,
+            )
+            .unwrap();
+            assert!(new_generation.contains(&solution), "{selection_strategy}");
+            assert_eq!(1.0, best_individuals(&new_generation, 1)[0].fitness());
+        }
+    }
+
+    #[test]
+    fn test_fixed_pieces_survive_every_strategy() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let fixed = Arc::new("1:6,2:0,7:3".parse::<FixedPieces>().unwrap());
+        let generation: Generation = Chromosome::create_generation(8, 30, &mut rng)
+            .into_iter()
+            .map(|ch| ch.with_fixed(Some(fixed.clone())))
+            .collect();
+        for selection_strategy in SelectionStrategy::value_variants() {
+            for mutation_strategy in MutationStrategy::value_variants() {
+                let new_generation = new_generation(
+                    *selection_strategy,
+                    generation.clone(),
+                    Probability(1.0),
+                    SelectionParams {
+                        mutation_strategy: *mutation_strategy,
+                        gene_mutation_probability: Probability(0.2),
+                        immigrant_rate: Probability(0.3),
+                        ..Default::default()
+                    },
+                    &mut rng,
+                )
+                .unwrap();
+                for ch in &new_generation {
+                    assert!(
+                        fixed.iter().all(|(x, y)| ch.genes()[x as usize] == y),
+                        "{selection_strategy}, {mutation_strategy}: {:?}",
+                        ch.genes()
+                    );
+                    let mut genes = ch.genes().to_vec();
+                    genes.sort_unstable();
+                    assert_eq!((0..8).collect::<Vec<_>>(), genes);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_elitism_full() {
+        let generation = Chromosome::create_generation(8, 10, &mut thread_rng());
+        let new_generation = new_generation(
+            SelectionStrategy::Tournament,
+            generation.clone(),
+            Probability(1.0),
+            SelectionParams {
+                elitism: 10,
+                ..Default::default()
+            },
+            &mut thread_rng(),
+        )
+        .unwrap();
+        assert_eq!(10, new_generation.len());
+        assert!(generation.iter().all(|ch| new_generation.contains(ch)));
+    }
+
+    #[test]
+    fn test_mutate_never() {
+        let original = Chromosome::new(8, &mut thread_rng());
+        for strategy in MutationStrategy::value_variants() {
+            let mut mutated = original.clone();
+            mutate(&mut mutated, *strategy, Probability(0.0), &mut thread_rng());
+            assert_eq!(original, mutated);
+        }
+    }
+
+    #[test]
+    fn test_mutate_inversion() {
+        let original = Chromosome::from((0..16).collect::<Vec<_>>());
+        for _ in 0..100 {
+            let mut mutated = original.clone();
+            mutated.mutate_invert(&mut thread_rng());
+
+            let differs = |i: &usize| original.genes[*i] != mutated.genes[*i];
+            let start = (0..16).find(differs).unwrap();
+            let end = (0..16).rfind(differs).unwrap();
+
+            let mut expected = original.genes[start..=end].to_vec();
+            expected.reverse();
+            assert_eq!(expected, mutated.genes[start..=end]);
+            assert_eq!(original.genes[..start], mutated.genes[..start]);
+            assert_eq!(original.genes[end + 1..], mutated.genes[end + 1..]);
+        }
+    }
+
+    #[test]
+    fn test_mutate_scramble() {
+        let original = Chromosome::new(16, &mut thread_rng());
+        let mut mutated = original.clone();
+        mutate_scramble(&mut mutated, &mut thread_rng());
+
+        let mut genes = mutated.genes.clone();
+        genes.sort_unstable();
+        assert_eq!((0..16).collect::<Vec<_>>(), genes);
+    }
+
+    #[test]
+    fn test_mutate_restart() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let original = Chromosome::new(16, &mut rng);
+        let mut mutated = original.clone();
+        mutate(
+            &mut mutated,
+            MutationStrategy::Restart,
+            Probability(1.0),
+            &mut rng,
+        );
+        assert_ne!(original, mutated);
+
+        let mut genes = mutated.genes.clone();
+        genes.sort_unstable();
+        assert_eq!((0..16).collect::<Vec<_>>(), genes);
+    }
+
+    #[test]
+    fn test_seeded_generations_are_identical() {
+        for selection_strategy in SelectionStrategy::value_variants() {
+            let run = || {
+                let mut rng = StdRng::seed_from_u64(42);
+                let mut generation = Chromosome::create_generation(8, 30, &mut rng);
+                for _ in 0..5 {
+                    generation = new_generation(
+                        *selection_strategy,
+                        generation,
+                        Probability(0.3),
+                        SelectionParams::default(),
+                        &mut rng,
+                    )
+                    .unwrap();
+                }
+                generation
+            };
+            assert_eq!(run(), run());
+        }
+    }
+
+    #[test]
+    fn test_seeded_children_are_identical() {
+        let parents = Chromosome::create_generation(12, 5, &mut StdRng::seed_from_u64(7));
+        let parents: Vec<&Chromosome> = parents.iter().collect();
+        for crossover_strategy in CrossoverStrategy::value_variants() {
+            for mutation_strategy in MutationStrategy::value_variants() {
+                let reproduction = Reproduction {
+                    crossover_strategy: *crossover_strategy,
+                    crossover_probability: Probability(0.7),
+                    parents: if *crossover_strategy == CrossoverStrategy::Majority {
+                        5
+                    } else {
+                        2
+                    },
+                    mutation_strategy: *mutation_strategy,
+                    mutation_probability: Probability(0.5),
+                    gene_mutation_probability: Probability(0.0),
+                };
+                let children = || {
+                    let mut rng = StdRng::seed_from_u64(42);
+                    (0..20)
+                        .map(|_| {
+                            crossover_many(&parents[..reproduction.parents], reproduction, &mut rng)
+                                .genes
+                        })
+                        .collect::<Vec<_>>()
+                };
+                assert_eq!(
+                    children(),
+                    children(),
+                    "{crossover_strategy} {mutation_strategy}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_steady_state() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut generation = Chromosome::create_generation(8, 20, &mut rng);
+        let best_fitness =
+            |generation: &Generation| generation.iter().map(Board::from).max().unwrap().fitness();
+
+        for elitism in [0, 2] {
+            let params = SelectionParams {
+                elitism,
+                ..Default::default()
+            };
+            for _ in 0..50 {
+                let previous_best = best_fitness(&generation);
+                let previous = generation.clone();
+                generation = new_generation(
+                    SelectionStrategy::SteadyState,
+                    generation,
+                    Probability(0.5),
+                    params,
+                    &mut rng,
+                )
+                .unwrap();
+                assert_eq!(20, generation.len());
+                assert!(best_fitness(&generation) >= previous_best);
+
+                let kept = previous.iter().filter(|ch| generation.contains(ch)).count();
+                assert!(kept >= 18);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pmx() {
+        let parent1 = [0, 1, 2, 3, 4, 5, 6, 7];
+        let parent2 = [3, 7, 5, 1, 6, 0, 2, 4];
+        let child = pmx(&parent1, &parent2, 3..6);
+        assert_eq!(vec![1, 7, 0, 3, 4, 5, 2, 6], child);
+        assert_eq!(parent1[3..6], child[3..6]);
+        assert!(is_permutation(&child));
+    }
+
+    #[test]
+    fn test_pmx_is_permutation() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for len in 1..20 {
+            let parent1 = Chromosome::new(len, &mut rng);
+            let parent2 = Chromosome::new(len, &mut rng);
+            let child = crossover_pmx(&parent1, &parent2, &mut rng);
+            assert!(is_permutation(&child.genes), "{child:?}");
+        }
+    }
+
+    #[test]
+    fn test_pmx_edge_cases() {
+        let parent1 = [0, 1, 2, 3, 4, 5];
+        let parent2 = [5, 3, 1, 0, 4, 2];
+        // the whole parent1 or none of it
+        assert_eq!(parent1.to_vec(), pmx(&parent1, &parent2, 0..6));
+        assert_eq!(parent2.to_vec(), pmx(&parent1, &parent2, 0..0));
+        assert_eq!(parent2.to_vec(), pmx(&parent1, &parent2, 6..6));
+        // the segments touching the ends
+        for segment in [0..1, 0..3, 3..6, 5..6] {
+            let child = pmx(&parent1, &parent2, segment.clone());
+            assert_eq!(parent1[segment.clone()], child[segment.clone()]);
+            assert!(is_permutation(&child), "{segment:?}: {child:?}");
+        }
+        // identical parents
+        for segment in [0..0, 1..4, 0..6] {
+            assert_eq!(parent2.to_vec(), pmx(&parent2, &parent2, segment));
+        }
+        // the smallest boards
+        assert_eq!(vec![0], pmx(&[0], &[0], 0..1));
+        for segment in [0..0, 0..1, 1..2, 0..2] {
+            let child = pmx(&[0, 1], &[1, 0], segment.clone());
+            assert!(is_permutation(&child), "{segment:?}: {child:?}");
+        }
+        assert_eq!(vec![0, 1], pmx(&[0, 1], &[1, 0], 0..1));
+        assert_eq!(vec![1, 0], pmx(&[0, 1], &[1, 0], 0..0));
+    }
+
+    #[test]
+    fn test_ox() {
+        let parent1 = [0, 1, 2, 3, 4, 5, 6, 7];
+        let parent2 = [3, 7, 5, 1, 6, 0, 2, 4];
+        let child = ox(&parent1, &parent2, 3..6);
+        assert_eq!(vec![7, 1, 6, 3, 4, 5, 0, 2], child);
+        assert!(is_permutation(&child));
+    }
+
+    #[test]
+    fn test_ox_is_permutation() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for len in 1..20 {
+            let parent1 = Chromosome::new(len, &mut rng);
+            let parent2 = Chromosome::new(len, &mut rng);
+            let child = crossover_ox(&parent1, &parent2, &mut rng);
+            assert!(is_permutation(&child.genes), "{child:?}");
+        }
+    }
+
+    #[test]
+    fn test_ox_keeps_segment() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for len in 1..20 {
+            let parent1 = Chromosome::new(len, &mut rng);
+            let parent2 = Chromosome::new(len, &mut rng);
+            let (a, b) = (
+                rng.gen_range(0..=len as usize),
+                rng.gen_range(0..=len as usize),
+            );
+            let segment = a.min(b)..a.max(b);
+            let child = ox(&parent1.genes, &parent2.genes, segment.clone());
+            assert_eq!(len as usize, child.len());
+            assert_eq!(parent1.genes[segment.clone()], child[segment]);
+            assert!(is_permutation(&child), "{child:?}");
+        }
+    }
+
+    #[test]
+    fn test_erx() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let parent = [3, 1, 0, 2, 5, 4];
+        assert_eq!(parent.to_vec(), erx(&parent, &parent, &mut rng));
+        assert_eq!(vec![0], erx(&[0], &[0], &mut rng));
+        assert!(erx(&[], &[], &mut rng).is_empty());
+
+        // every pair of the adjacent genes of the child is adjacent in a parent, if possible
+        let parent1 = [0, 1, 2, 3, 4, 5];
+        let parent2 = [5, 4, 3, 2, 1, 0];
+        let child = erx(&parent1, &parent2, &mut rng);
+        assert!(child.windows(2).all(|pair| pair[0].abs_diff(pair[1]) == 1));
+    }
+
+    #[test]
+    fn test_erx_is_permutation() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for len in 2..64 {
+            for _ in 0..10 {
+                let parent1 = Chromosome::new(len, &mut rng);
+                let parent2 = Chromosome::new(len, &mut rng);
+                let child = crossover_erx(&parent1, &parent2, &mut rng);
+                assert_eq!(len as usize, child.genes.len());
+                assert!(is_permutation(&child.genes), "{child:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_cx() {
+        let parent1 = Chromosome::from(vec![0, 1, 2, 3, 4, 5, 6, 7]);
+        let parent2 = Chromosome::from(vec![1, 0, 3, 2, 5, 4, 7, 6]);
+        assert_eq!(
+            vec![0, 0, 1, 1, 2, 2, 3, 3],
+            cycles(&parent1.genes, &parent2.genes)
+        );
+        let child = crossover_cx(&parent1, &parent2);
+        assert_eq!(vec![0, 1, 3, 2, 4, 5, 7, 6], child.genes);
+
+        let parent2 = Chromosome::from(vec![3, 7, 5, 1, 6, 0, 2, 4]);
+        assert_eq!(vec![0; 8], cycles(&parent1.genes, &parent2.genes));
+        assert_eq!(parent1, crossover_cx(&parent1, &parent2));
+    }
+
+    #[test]
+    fn test_cx_keeps_positions() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for len in 1..20 {
+            let parent1 = Chromosome::new(len, &mut rng);
+            let parent2 = Chromosome::new(len, &mut rng);
+            let child = crossover_cx(&parent1, &parent2);
+            assert!(is_permutation(&child.genes), "{child:?}");
+            assert!(child
+                .genes
+                .iter()
+                .zip(parent1.genes.iter().zip(&parent2.genes))
+                .all(|(gene, (gene1, gene2))| gene == gene1 || gene == gene2));
+            assert_eq!(parent1, crossover_cx(&parent1, &parent1));
+        }
+    }
+
+    #[test]
+    fn test_crowding_keeps_better_parents() {
+        let mut generation = vec![Chromosome::from(vec![1, 3, 0, 2]); 10];
+        generation.extend(vec![Chromosome::from(vec![2, 0, 3, 1]); 10]);
+        let new_generation = new_generation(
+            SelectionStrategy::Crowding,
+            generation,
+            Probability(1.0),
+            SelectionParams {
+                elitism: 0,
+                ..Default::default()
+            },
+            &mut thread_rng(),
+        )
+        .unwrap();
+        assert_eq!(20, new_generation.len());
+        assert!(new_generation.iter().all(|ch| ch.fitness() == 1.0));
+    }
+
+    #[test]
+    fn test_mismatched_lengths() {
+        let mut generation = Chromosome::create_generation(5, 10, &mut thread_rng());
+        generation.push(Chromosome::new(6, &mut thread_rng()));
+        for strategy in SelectionStrategy::value_variants() {
+            assert_eq!(
+                Err(SelectionError::MismatchedLengths {
+                    expected: 5,
+                    found: 6
+                }),
+                new_generation(
+                    *strategy,
+                    generation.clone(),
+                    Probability(0.5),
+                    SelectionParams::default(),
+                    &mut thread_rng(),
+                ),
+                "{strategy}"
+            );
+        }
+    }
+
+    #[test]
     fn test_crossover() {
-        let parent1 = Chromosome::new(5);
-        let parent2 = Chromosome::new(5);
-        let crossover = crossover(&parent1, &parent2, random());
+        let parent1 = Chromosome::new(5, &mut thread_rng());
+        let parent2 = Chromosome::new(5, &mut thread_rng());
+        let crossover = crossover(
+            &parent1,
+            &parent2,
+            Reproduction {
+                crossover_strategy: CrossoverStrategy::SimilarGenes,
+                crossover_probability: Probability(1.0),
+                parents: 2,
+                mutation_strategy: MutationStrategy::Swap,
+                mutation_probability: random(),
+                gene_mutation_probability: Probability(0.0),
+            },
+            &mut thread_rng(),
+        );
         println!("{parent1:#?}\n{parent2:#?}\n{crossover:#?}");
         assert_eq!(5, crossover.genes.len());
     }
+
+    #[test]
+    fn test_majority_of_three() {
+        let parents: [&[Gene]; 3] = [
+            &[0, 1, 2, 3, 4, 5],
+            &[0, 2, 1, 3, 5, 4],
+            &[1, 0, 2, 4, 3, 5],
+        ];
+        for seed in 0..10 {
+            let child = majority(&parents, 6, &mut StdRng::seed_from_u64(seed));
+            assert!(is_permutation(&child), "{child:?}");
+            assert_eq!([0, 2, 3, 5], [child[0], child[2], child[3], child[5]]);
+        }
+    }
+
+    #[test]
+    fn test_majority_of_five() {
+        let parents: [&[Gene]; 5] = [
+            &[0, 1, 2, 3, 4, 5],
+            &[0, 1, 3, 2, 5, 4],
+            &[0, 2, 1, 3, 4, 5],
+            &[1, 0, 2, 3, 5, 4],
+            &[2, 1, 0, 4, 3, 5],
+        ];
+        for seed in 0..10 {
+            let child = majority(&parents, 6, &mut StdRng::seed_from_u64(seed));
+            assert!(is_permutation(&child), "{child:?}");
+            assert_eq!([0, 1, 3, 5], [child[0], child[1], child[3], child[5]]);
+        }
+    }
+
+    #[test]
+    fn test_crossover_similar_genes_seeded() {
+        let parent1 = Chromosome::from(vec![5, 10, 1, 0, 3, 8, 6, 9, 2, 4, 7, 11]);
+        let parent2 = Chromosome::from(vec![5, 10, 2, 9, 6, 8, 3, 0, 1, 4, 7, 11]);
+        for (seed, expected) in [
+            [5, 10, 3, 1, 2, 8, 0, 9, 6, 4, 7, 11],
+            [5, 10, 0, 9, 1, 8, 3, 2, 6, 4, 7, 11],
+            [5, 10, 3, 2, 6, 8, 9, 1, 0, 4, 7, 11],
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let child = crossover_similar_genes(
+                &parent1,
+                &parent2,
+                &mut StdRng::seed_from_u64(seed as u64),
+            );
+            assert_eq!(expected, child.genes[..]);
+        }
+    }
+
+    #[test]
+    fn test_crossover_similar_genes_is_permutation() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for n in [1, 2, 5, 8, 33, 512] {
+            for _ in 0..10 {
+                let parent1 = Chromosome::new(n, &mut rng);
+                let mut parent2 = parent1.clone();
+                // mostly similar parents as well as unrelated ones
+                for _ in 0..rng.gen_range(0..=n) {
+                    let (i, j) = (rng.gen_range(0..n as usize), rng.gen_range(0..n as usize));
+                    parent2.genes_mut().swap(i, j);
+                }
+                let unrelated = Chromosome::new(n, &mut rng);
+                for other in [&parent2, &unrelated] {
+                    let child = crossover_similar_genes(&parent1, other, &mut rng);
+                    assert!(is_permutation(child.genes()), "{:?}", child.genes());
+                    for ((gene, p1), p2) in
+                        child.genes().iter().zip(parent1.genes()).zip(other.genes())
+                    {
+                        assert!(p1 != p2 || gene == p1);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_majority_rectangular() {
+        let mut rng = StdRng::seed_from_u64(42);
+        // the taller board keeps the rows distinct
+        for _ in 0..20 {
+            let parents: Vec<Chromosome> = (0..3)
+                .map(|_| Chromosome::new_rectangular(5, 9, &mut rng))
+                .collect();
+            let genes: Vec<&[Gene]> = parents.iter().map(|ch| ch.genes()).collect();
+            let child = majority(&genes, 9, &mut rng);
+            assert_eq!(5, child.len());
+            assert!(child.iter().all(|gene| *gene < 9));
+            assert_eq!(5, child.iter().collect::<HashSet<_>>().len(), "{child:?}");
+        }
+        // the wider board uses every row the same amount of times
+        for _ in 0..20 {
+            let parents: Vec<Chromosome> = (0..3)
+                .map(|_| Chromosome::new_rectangular(9, 3, &mut rng))
+                .collect();
+            let genes: Vec<&[Gene]> = parents.iter().map(|ch| ch.genes()).collect();
+            let mut child = majority(&genes, 3, &mut rng);
+            child.sort_unstable();
+            assert_eq!(vec![0, 0, 0, 1, 1, 1, 2, 2, 2], child);
+        }
+    }
+
+    #[test]
+    fn test_multi_parent_selection() {
+        for strategy in [
+            SelectionStrategy::Tournament,
+            SelectionStrategy::Roulette,
+            SelectionStrategy::Rank,
+            SelectionStrategy::Sus,
+            SelectionStrategy::SteadyState,
+        ] {
+            let generation = Chromosome::create_generation(8, 20, &mut thread_rng());
+            let new_generation = new_generation(
+                strategy,
+                generation,
+                Probability(0.1),
+                SelectionParams {
+                    crossover_strategy: CrossoverStrategy::Majority,
+                    parents: 5,
+                    ..Default::default()
+                },
+                &mut StdRng::seed_from_u64(42),
+            )
+            .unwrap();
+            assert_eq!(20, new_generation.len(), "{strategy}");
+            assert!(
+                new_generation.iter().all(|ch| is_permutation(ch.genes())),
+                "{strategy}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_crossover_probability() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let parent1 = Chromosome::from(vec![1, 3, 0, 2, 4, 5]);
+        let parent2 = Chromosome::from(vec![5, 4, 3, 2, 1, 0]);
+        assert!(parent1.fitness() > parent2.fitness());
+        let reproduction = |crossover_probability| Reproduction {
+            crossover_strategy: CrossoverStrategy::Pmx,
+            crossover_probability: Probability(crossover_probability),
+            parents: 2,
+            mutation_strategy: MutationStrategy::Swap,
+            mutation_probability: Probability(0.0),
+            gene_mutation_probability: Probability(0.0),
+        };
+
+        for _ in 0..20 {
+            assert_eq!(
+                parent1,
+                crossover(&parent2, &parent1, reproduction(0.0), &mut rng)
+            );
+        }
+
+        // the certain crossover is the same as before the crossover probability
+        for seed in 0..20 {
+            assert_eq!(
+                crossover_pmx(&parent1, &parent2, &mut StdRng::seed_from_u64(seed)),
+                crossover(
+                    &parent1,
+                    &parent2,
+                    reproduction(1.0),
+                    &mut StdRng::seed_from_u64(seed)
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn test_immigrant_rate() {
+        let original = Chromosome::from((0..20).collect::<Vec<Gene>>());
+        for (immigrant_rate, expected) in [(0.0, 0), (0.5, 10), (1.0, 20)] {
+            let generation = vec![original.clone(); 22];
+            let new_generation = new_generation(
+                SelectionStrategy::Tournament,
+                generation,
+                Probability(0.0),
+                SelectionParams {
+                    immigrant_rate: Probability(immigrant_rate),
+                    ..Default::default()
+                },
+                &mut StdRng::seed_from_u64(42),
+            )
+            .unwrap();
+            assert_eq!(22, new_generation.len());
+            assert_eq!(
+                expected,
+                new_generation.iter().filter(|ch| **ch != original).count()
+            );
+        }
+    }
+
+    #[test]
+    fn test_kill_the_half_survivors() {
+        // sorted by fitness: 0, 2, 3, 4, 5, 6, 7 and 8 beaten queens
+        let sorted: Vec<Chromosome> = [
+            vec![0, 4, 7, 5, 2, 6, 1, 3],
+            vec![0, 1, 3, 5, 7, 2, 4, 6],
+            vec![0, 2, 4, 5, 7, 1, 3, 6],
+            vec![0, 1, 2, 3, 5, 7, 4, 6],
+            vec![0, 1, 3, 4, 5, 7, 2, 6],
+            vec![0, 1, 2, 3, 5, 7, 6, 4],
+            vec![0, 1, 2, 3, 4, 6, 7, 5],
+            vec![0, 1, 2, 3, 4, 5, 6, 7],
+        ]
+        .into_iter()
+        .map(Chromosome::from)
+        .collect();
+        let shuffled: Generation = [5, 2, 7, 0, 3, 6, 1, 4]
+            .into_iter()
+            .map(|i| sorted[i].clone())
+            .collect();
+        let reproduction = Reproduction {
+            crossover_strategy: CrossoverStrategy::SimilarGenes,
+            crossover_probability: Probability(1.0),
+            parents: 2,
+            mutation_strategy: MutationStrategy::Swap,
+            mutation_probability: Probability(0.0),
+            gene_mutation_probability: Probability(0.0),
+        };
+
+        // no elites: the better half survives as is
+        let mut generation = shuffled.clone();
+        kill_the_half_impl(
+            &mut generation,
+            reproduction,
+            8,
+            &mut StdRng::seed_from_u64(42),
+        );
+        assert_eq!(8, generation.len());
+        assert_eq!(sorted[..4], generation[..4]);
+
+        // two elites: the rest of the better half survives
+        let mut generation = shuffled.clone();
+        kill_the_half_impl(
+            &mut generation,
+            reproduction,
+            6,
+            &mut StdRng::seed_from_u64(42),
+        );
+        assert_eq!(6, generation.len());
+        assert_eq!(sorted[2..4], generation[..2]);
+
+        // the elites take the whole better half
+        let mut generation = shuffled;
+        kill_the_half_impl(
+            &mut generation,
+            reproduction,
+            3,
+            &mut StdRng::seed_from_u64(42),
+        );
+        assert_eq!(3, generation.len());
+        assert!(!generation.contains(&sorted[2]) && !generation.contains(&sorted[3]));
+    }
+
+    #[test]
+    fn test_tiny_populations() {
+        for strategy in SelectionStrategy::value_variants() {
+            for population_size in 0..=2 {
+                let generation =
+                    Chromosome::create_generation(5, population_size, &mut thread_rng());
+                let new_generation = new_generation(
+                    *strategy,
+                    generation,
+                    random(),
+                    SelectionParams {
+                        elitism: 0,
+                        ..Default::default()
+                    },
+                    &mut thread_rng(),
+                );
+                let min_population_size = match strategy {
+                    SelectionStrategy::AdamAndEve
+                    | SelectionStrategy::KillTheHalf
+                    | SelectionStrategy::WeightedPairs => 2,
+                    _ => 1,
+                };
+                if population_size < min_population_size {
+                    assert_eq!(
+                        Err(SelectionError::PopulationTooSmall {
+                            population_size,
+                            min_population_size
+                        }),
+                        new_generation,
+                        "{strategy:?}"
+                    );
+                } else {
+                    assert_eq!(population_size, new_generation.unwrap().len());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_mu_lambda_size() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for selection_strategy in [
+            SelectionStrategy::MuPlusLambda,
+            SelectionStrategy::MuCommaLambda,
+        ] {
+            for (mu, lambda, elitism) in [(20, 20, 0), (20, 50, 2), (30, 10, 0), (5, 1, 1)] {
+                let mut generation = Chromosome::create_generation(8, mu, &mut rng);
+                for _ in 0..5 {
+                    generation = new_generation(
+                        selection_strategy,
+                        generation,
+                        Probability(0.3),
+                        SelectionParams {
+                            elitism,
+                            lambda: NonZeroUsize::new(lambda).unwrap(),
+                            ..Default::default()
+                        },
+                        &mut rng,
+                    )
+                    .unwrap();
+                    assert_eq!(
+                        mu,
+                        generation.len(),
+                        "{selection_strategy} μ={mu} λ={lambda}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_mu_plus_lambda_keeps_the_best() {
+        let solution = Chromosome::from(vec![1, 3, 0, 2]);
+        let mut generation = vec![Chromosome::from(vec![0, 1, 2, 3]); 9];
+        generation.push(solution.clone());
+        let new_generation = new_generation(
+            SelectionStrategy::MuPlusLambda,
+            generation,
+            Probability(1.0),
+            SelectionParams {
+                elitism: 0,
+                lambda: NonZeroUsize::new(5).unwrap(),
+                ..Default::default()
+            },
+            &mut StdRng::seed_from_u64(42),
+        )
+        .unwrap();
+        assert!(new_generation.contains(&solution));
+    }
+
+    #[test]
+    fn test_generation_gap() {
+        let run = |params| {
+            let mut rng = StdRng::seed_from_u64(42);
+            let mut generation = Chromosome::create_generation(8, 50, &mut rng);
+            for _ in 0..5 {
+                generation = new_generation(
+                    SelectionStrategy::Tournament,
+                    generation,
+                    Probability(0.3),
+                    params,
+                    &mut rng,
+                )
+                .unwrap();
+            }
+            generation
+        };
+        assert_eq!(
+            run(SelectionParams::default()),
+            run(SelectionParams {
+                generation_gap: GenerationGap(1.0),
+                ..Default::default()
+            })
+        );
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut generation = Chromosome::create_generation(8, 100, &mut rng);
+        for _ in 0..10 {
+            let previous = generation.clone();
+            generation = new_generation(
+                SelectionStrategy::Tournament,
+                generation,
+                Probability(1.0),
+                SelectionParams {
+                    generation_gap: GenerationGap(0.1),
+                    ..Default::default()
+                },
+                &mut rng,
+            )
+            .unwrap();
+            assert_eq!(100, generation.len());
+            let kept = previous.iter().filter(|ch| generation.contains(ch)).count();
+            assert!(kept >= 90, "{kept}");
+        }
+    }
+
+    #[test]
+    fn test_weighted_pairs_distinct_parents() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let generation = Chromosome::create_generation(8, 100, &mut rng);
+        let fitness: Vec<f32> = generation.iter().map(Chromosome::fitness).collect();
+        let wheel = RouletteWheel::new(&fitness);
+
+        let pairs: HashSet<(usize, usize)> = (0..100)
+            .map(|_| wheel.spin_pair(&mut rng))
+            .inspect(|(parent1, parent2)| assert_ne!(parent1, parent2))
+            .collect();
+        assert!(pairs.len() > 2, "{}", pairs.len());
+
+        let best = best_individuals(&generation, 1).pop().unwrap();
+        let next = new_generation(
+            SelectionStrategy::WeightedPairs,
+            generation,
+            Probability(0.3),
+            SelectionParams::default(),
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(100, next.len());
+        assert!(next.contains(&best));
+    }
 }
@@ -0,0 +1,272 @@
+use std::{fs, path::Path};
+
+use anyhow::{bail, ensure, Context};
+
+use crate::{
+    board::Board,
+    chromosome::{Chromosome, Gene},
+    config::Config,
+    selection::Generation,
+    solver::{Progress, SolverState},
+};
+
+pub use crate::io::{load_generation, save_generation};
+
+/// Saved state of the search together with the config, that produced it
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Checkpoint {
+    pub config: Config,
+    pub state: SolverState,
+}
+
+impl Checkpoint {
+    #[inline]
+    pub fn new(config: &Config, progress: Progress<'_>) -> Self {
+        Self {
+            config: config.clone(),
+            state: progress.into(),
+        }
+    }
+
+    /// Writes the checkpoint as JSON, replacing the file atomically,
+    /// so that an interruption never leaves a broken checkpoint behind
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let json = serde_json::to_string(self)?;
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, json)
+            .with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+        fs::rename(&tmp_path, path)
+            .with_context(|| format!("Failed to replace {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Reads the checkpoint, validating the saved config and checking the chromosomes against its board size
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let checkpoint: Self = serde_json::from_str(&json)?;
+        checkpoint.config.validate()?;
+        validate_generation(
+            &checkpoint.state.generation,
+            checkpoint.config.pieces(),
+            checkpoint.config.board_height(),
+        )?;
+        Ok(checkpoint)
+    }
+}
+
+/// Serializes the generation as a JSON array of the chromosomes
+#[inline]
+pub fn generation_to_json(generation: &Generation) -> serde_json::Result<String> {
+    serde_json::to_string(generation)
+}
+
+/// Deserializes the generation from JSON, checking that every chromosome
+/// has exactly `board_size` genes within the range `0..board_size`
+pub fn generation_from_json(json: &str, board_size: u16) -> anyhow::Result<Generation> {
+    let generation: Generation = serde_json::from_str(json)?;
+    validate_generation(&generation, board_size, board_size)?;
+    Ok(generation)
+}
+
+/// Board together with its evaluation, e.g. for reporting the individuals
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BoardSnapshot {
+    pub genes: Vec<Gene>,
+    pub beats_count: u16,
+    pub fitness: f32,
+}
+
+impl From<&Chromosome> for BoardSnapshot {
+    fn from(chromosome: &Chromosome) -> Self {
+        Self {
+            genes: chromosome.genes().to_vec(),
+            beats_count: Board::from(chromosome).beats_count(),
+            fitness: chromosome.fitness(),
+        }
+    }
+}
+
+pub(crate) fn validate_generation(
+    generation: &Generation,
+    pieces: u16,
+    height: u16,
+) -> anyhow::Result<()> {
+    for (i, chromosome) in generation.iter().enumerate() {
+        validate(chromosome, pieces, height)
+            .map_err(|e| e.context(format!("Invalid chromosome #{i}")))?;
+    }
+    Ok(())
+}
+
+fn validate(chromosome: &Chromosome, pieces: u16, height: u16) -> anyhow::Result<()> {
+    let genes = chromosome.genes();
+    ensure!(
+        chromosome.height() == height,
+        "Expected {height} rows, got {}",
+        chromosome.height()
+    );
+    ensure!(
+        genes.len() == pieces as usize,
+        "Expected {pieces} genes, got {}",
+        genes.len()
+    );
+    if let Some(gene) = genes.iter().find(|gene| **gene >= height) {
+        bail!("Gene {gene} is out of range 0..{height}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::chromosome::Chromosome;
+
+    use clap::Parser;
+
+    use crate::{
+        config::Config,
+        solver::{self, Progress, Solver},
+    };
+
+    use super::{generation_from_json, generation_to_json, BoardSnapshot, Checkpoint};
+
+    #[test]
+    fn test_round_trip() {
+        let generation = Chromosome::create_generation(8, 10, &mut StdRng::seed_from_u64(42));
+        let json = generation_to_json(&generation).unwrap();
+        assert_eq!(generation, generation_from_json(&json, 8).unwrap());
+    }
+
+    #[test]
+    fn test_board_snapshot() {
+        let snapshot = BoardSnapshot::from(&Chromosome::from(vec![0, 1, 3, 2]));
+        assert_eq!(
+            BoardSnapshot {
+                genes: vec![0, 1, 3, 2],
+                beats_count: 4,
+                fitness: 0.2,
+            },
+            snapshot
+        );
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert_eq!(r#"{"genes":[0,1,3,2],"beats_count":4,"fitness":0.2}"#, json);
+        assert_eq!(snapshot, serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        let generation = vec![Chromosome::from(vec![0, 1, 2, 3])];
+        let json = generation_to_json(&generation).unwrap();
+        assert!(generation_from_json(&json, 3).is_err());
+        assert!(generation_from_json(&json, 5).is_err());
+        assert!(generation_from_json(&json, 4).is_ok());
+    }
+
+    #[test]
+    fn test_resumed_best_fitness() {
+        let config = Config::parse_from([
+            "",
+            "-b",
+            "20",
+            "-s",
+            "Tournament",
+            "--max-generations",
+            "15",
+            "--checkpoint-every",
+            "5",
+            "--seed",
+            "7",
+        ]);
+        let best_fitness =
+            |progress: Progress<'_>| solver::best_individual(progress.generation).fitness();
+
+        let mut checkpoint = None;
+        let mut uninterrupted = Vec::new();
+        Solver::new(&config)
+            .solve_with_progress(|progress: Progress<'_>| {
+                if progress.generation_count == config.checkpoint_interval.get() {
+                    checkpoint = Some(Checkpoint::new(&config, progress));
+                }
+                uninterrupted.push(best_fitness(progress));
+            })
+            .unwrap();
+
+        let checkpoint = checkpoint.unwrap();
+        let mut resumed = Vec::new();
+        Solver::new(&checkpoint.config)
+            .resume_from(checkpoint.state)
+            .solve_with_progress(|progress: Progress<'_>| resumed.push(best_fitness(progress)))
+            .unwrap();
+        assert_eq!(15, uninterrupted.len());
+        assert_eq!(uninterrupted[4..], resumed);
+    }
+
+    #[test]
+    fn test_resume_from_checkpoint() {
+        let config = Config::parse_from([
+            "",
+            "-b",
+            "12",
+            "-g",
+            "30",
+            "--max-generations",
+            "40",
+            "--seed",
+            "42",
+        ]);
+
+        let mut uninterrupted = None;
+        let mut checkpoint = None;
+        Solver::new(&config)
+            .solve_with_progress(|progress: Progress<'_>| {
+                if progress.generation_count == 15 {
+                    checkpoint = Some(Checkpoint::new(&config, progress));
+                }
+                uninterrupted = Some(progress.generation.clone());
+            })
+            .unwrap();
+
+        let path =
+            std::env::temp_dir().join(format!("queen-placement-{}.json", std::process::id()));
+        checkpoint.unwrap().save(&path).unwrap();
+        let checkpoint = Checkpoint::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let mut resumed = None;
+        let outcome = Solver::new(&checkpoint.config)
+            .resume_from(checkpoint.state)
+            .solve_with_progress(|progress: Progress<'_>| {
+                resumed = Some(progress.generation.clone());
+            })
+            .unwrap();
+        assert_eq!(40, outcome.generation_count);
+        assert_eq!(uninterrupted.unwrap(), resumed.unwrap());
+    }
+
+    #[test]
+    fn test_load_invalid_config() {
+        let mut config = Config::parse_from(["", "-b", "6", "-g", "10", "--max-generations", "1"]);
+        let mut checkpoint = None;
+        Solver::new(&config)
+            .solve_with_progress(|progress: Progress<'_>| {
+                checkpoint = Some(Checkpoint::new(&config, progress));
+            })
+            .unwrap();
+        let mut checkpoint = checkpoint.unwrap();
+        config.elitism = 10;
+        checkpoint.config = config;
+
+        let path = std::env::temp_dir().join(format!(
+            "queen-placement-invalid-{}.json",
+            std::process::id()
+        ));
+        checkpoint.save(&path).unwrap();
+        let loaded = Checkpoint::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(loaded.is_err());
+    }
+}
@@ -0,0 +1,1207 @@
+use std::{collections::HashSet, mem, num::NonZeroU32, sync::Arc, time::Instant};
+
+use rayon::prelude::{
+    IntoParallelIterator, IntoParallelRefIterator, ParallelExtend, ParallelIterator,
+};
+
+use crate::{
+    board::{self, BlockedCells, Board},
+    chromosome::Chromosome,
+    config::Config,
+    rng::SolverRng,
+    selection::{
+        self, Generation, Probability, Selection, SelectionCtx, SelectionError, SelectionParams,
+        SelectionStrategy,
+    },
+    stats::{self, GenerationStats},
+};
+
+/// Reason the evolutionary search has stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    /// The targeted amount of distinct fitting individuals was found (see [`Config::solutions_target`])
+    Solved,
+    /// `max_generations` were produced without finding enough fitting individuals
+    ExhaustedGenerations,
+    /// `time_limit` has elapsed without finding enough fitting individuals
+    TimeLimit,
+    /// The best fitness has not improved for `stagnation_generations`
+    Stagnation,
+}
+
+/// Result of the evolutionary search
+#[derive(Debug, Clone)]
+pub struct GaOutcome {
+    pub termination: Termination,
+    /// Distinct fitting individuals, found in all the generations. With `unique_up_to_symmetry`,
+    /// only the first found one of the individuals with the same canonical form.
+    /// Empty, if the solution was not found
+    pub solutions: HashSet<Chromosome>,
+    /// The fittest individual of the last generation
+    pub best: Chromosome,
+    /// Amount of the generations, including the initial one
+    pub generation_count: u32,
+    /// Amount of the individuals, that have been created and evaluated
+    pub evaluations: u64,
+    /// The best fitness of every generation, that has been evaluated by this run, starting with the initial
+    /// or the resumed one. Empty, unless recorded with [`Solver::with_fitness_history`]
+    pub fitness_history: Vec<f32>,
+}
+
+/// State of the search after each generation
+#[derive(Debug, Clone, Copy)]
+pub struct Progress<'g> {
+    pub generation: &'g Generation,
+    pub generation_count: u32,
+    pub evaluations: u64,
+    /// State of the generator, that will produce the next generation
+    pub rng: &'g SolverRng,
+}
+
+impl Progress<'_> {
+    /// Statistics of the current generation
+    #[inline]
+    pub fn stats(&self) -> GenerationStats {
+        stats::stats(self.generation_count, self.generation)
+    }
+}
+
+/// Everything needed to continue the search from some generation
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SolverState {
+    pub generation: Generation,
+    pub generation_count: u32,
+    pub evaluations: u64,
+    pub rng: SolverRng,
+}
+
+impl From<Progress<'_>> for SolverState {
+    fn from(progress: Progress<'_>) -> Self {
+        Self {
+            generation: progress.generation.clone(),
+            generation_count: progress.generation_count,
+            evaluations: progress.evaluations,
+            rng: progress.rng.clone(),
+        }
+    }
+}
+
+/// Runs the generations until a solution is found, `max_generations` is reached or `time_limit` elapses.
+///
+/// The `config` is expected to be valid (see [`Config::validate`]).
+/// Fails, if the generation is too small for the selection strategy
+#[inline]
+pub fn solve(config: &Config) -> Result<GaOutcome, SelectionError> {
+    Solver::new(config).solve()
+}
+
+/// Same as [`solve`], but calls `on_generation` after every generation, including the initial one
+#[inline]
+pub fn solve_with_progress(
+    config: &Config,
+    on_generation: impl FnMut(Progress<'_>),
+) -> Result<GaOutcome, SelectionError> {
+    Solver::new(config).solve_with_progress(on_generation)
+}
+
+/// Same as [`solve`], but calls `on_generation` with the statistics of every generation, including the initial one.
+///
+/// The statistics are computed in parallel, only the callback itself is called sequentially
+#[inline]
+pub fn solve_with_stats(
+    config: &Config,
+    on_generation: impl FnMut(&GenerationStats),
+) -> Result<GaOutcome, SelectionError> {
+    Solver::new(config).solve_with_stats(on_generation)
+}
+
+/// Runs the search and returns only the distinct solutions, collected from all the generations.
+///
+/// Meant for the `collect_all` configs, that stop at a generation or time limit rather than at the first solution
+#[inline]
+pub fn collect_solutions(config: &Config) -> Result<HashSet<Chromosome>, SelectionError> {
+    solve(config).map(|outcome| outcome.solutions)
+}
+
+/// Runner of the evolutionary search with a replaceable [`Selection`]
+pub struct Solver<'c> {
+    config: &'c Config,
+    selection: Box<dyn Selection>,
+    initial_state: Option<SolverState>,
+    records_fitness_history: bool,
+    blocked: Option<Arc<BlockedCells>>,
+}
+
+impl<'c> Solver<'c> {
+    /// Creates the solver with the selection strategy and the blocked cells from the `config`.
+    ///
+    /// The `config` is expected to be valid (see [`Config::validate`]), otherwise the blocked cells,
+    /// that fail to load, are ignored
+    pub fn new(config: &'c Config) -> Self {
+        Self {
+            config,
+            selection: config.selection_strategy.selection(),
+            initial_state: None,
+            records_fitness_history: false,
+            blocked: config.blocked_cells().ok().flatten().map(Arc::new),
+        }
+    }
+
+    /// Continues the search from the `state` instead of a random generation.
+    ///
+    /// With the same config, the search proceeds exactly as the one, that the `state` was taken from.
+    /// Only the stagnation detection and the time limit start over
+    pub fn resume_from(mut self, state: SolverState) -> Self {
+        self.initial_state = Some(state);
+        self
+    }
+
+    /// Replaces the selection strategy from the config with a custom one
+    pub fn with_selection(mut self, selection: Box<dyn Selection>) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Records the best fitness of every generation into [`GaOutcome::fitness_history`]
+    pub fn with_fitness_history(mut self) -> Self {
+        self.records_fitness_history = true;
+        self
+    }
+
+    /// Penalizes the pieces on the `blocked` cells of both the random and the resumed generation,
+    /// instead of the ones from the config
+    pub fn with_blocked_cells(mut self, blocked: BlockedCells) -> Self {
+        self.blocked = Some(Arc::new(blocked));
+        self
+    }
+
+    /// See [`solve`]
+    #[inline]
+    pub fn solve(self) -> Result<GaOutcome, SelectionError> {
+        self.solve_with_progress(|_| {})
+    }
+
+    /// See [`solve_with_stats`]
+    #[inline]
+    pub fn solve_with_stats(
+        self,
+        mut on_generation: impl FnMut(&GenerationStats),
+    ) -> Result<GaOutcome, SelectionError> {
+        self.solve_with_progress(|progress| on_generation(&progress.stats()))
+    }
+
+    /// See [`solve_with_progress`]
+    pub fn solve_with_progress(
+        mut self,
+        mut on_generation: impl FnMut(Progress<'_>),
+    ) -> Result<GaOutcome, SelectionError> {
+        let config = self.config;
+        let mut fitness_history = Vec::new();
+        let records_fitness_history = self.records_fitness_history;
+        let mut on_generation = |progress: Progress<'_>| {
+            if records_fitness_history {
+                fitness_history.push(best_individual(progress.generation).fitness());
+            }
+            on_generation(progress);
+        };
+        let start = Instant::now();
+        let selection_params = config.selection_params();
+        let SolverState {
+            mut generation,
+            mut generation_count,
+            mut evaluations,
+            mut rng,
+        } = match self.initial_state.take() {
+            // the fixed pieces are not saved together with the chromosomes
+            Some(state) => SolverState {
+                generation: pin_fixed(config, state.generation),
+                ..state
+            },
+            None => {
+                let mut rng = config.rng();
+                SolverState {
+                    generation: initial_generation(config, &mut rng),
+                    generation_count: 1,
+                    evaluations: config.generation_size.get() as u64,
+                    rng,
+                }
+            }
+        };
+        if self.blocked.is_some() {
+            generation = generation
+                .into_par_iter()
+                .map(|ch| ch.with_blocked(self.blocked.clone()))
+                .collect();
+        }
+        self.selection.check_population_size(generation.len())?;
+        selection::check_lengths(&generation)?;
+        let evaluations_per_generation = self
+            .selection
+            .evaluations_per_generation(config.generation_size.get(), selection_params)
+            as u64;
+        on_generation(Progress {
+            generation: &generation,
+            generation_count,
+            evaluations,
+            rng: &rng,
+        });
+
+        let mutation = MutationControl::new(config);
+        let mut stagnation = config
+            .stagnation_generations
+            .map(|window| StagnationDetector::new(window, config.stagnation_epsilon));
+
+        let solutions_target = config.solutions_target();
+        let mut solutions = HashSet::new();
+        // canonical forms of the `solutions`, that are only the keys, since they may move
+        // the fixed pieces or put the pieces onto the blocked cells
+        let mut symmetry_classes = HashSet::new();
+        let termination = loop {
+            let stagnated = stagnation
+                .as_mut()
+                .is_some_and(|detector| detector.update(&generation));
+            let fitting = generation
+                .par_iter()
+                .filter(|ch| Board::from(*ch).is_solution());
+            if config.unique_up_to_symmetry {
+                let keyed: Vec<(Chromosome, &Chromosome)> =
+                    fitting.map(|ch| (board::canonical_form(ch), ch)).collect();
+                for (canonical, solution) in keyed {
+                    if symmetry_classes.insert(canonical) {
+                        solutions.insert(solution.clone());
+                    }
+                }
+            } else {
+                solutions.par_extend(fitting.cloned());
+            }
+            if solutions_target.is_some_and(|target| solutions.len() >= target) {
+                break Termination::Solved;
+            }
+            if config
+                .max_generations
+                .is_some_and(|max| generation_count >= max.get())
+            {
+                break Termination::ExhaustedGenerations;
+            }
+            if config
+                .time_limit
+                .is_some_and(|limit| start.elapsed() >= limit)
+            {
+                break Termination::TimeLimit;
+            }
+            if stagnated {
+                break Termination::Stagnation;
+            }
+
+            let mutation_probability = mutation.probability(generation_count - 1, &generation);
+            generation = self.selection.next_generation(
+                generation,
+                &mut SelectionCtx {
+                    mutation_probability,
+                    params: selection_params,
+                    rng: &mut rng,
+                },
+            );
+            generation_count += 1;
+            evaluations += evaluations_per_generation;
+            on_generation(Progress {
+                generation: &generation,
+                generation_count,
+                evaluations,
+                rng: &rng,
+            });
+        };
+
+        let best = best_individual(&generation).clone();
+        Ok(GaOutcome {
+            termination,
+            solutions,
+            best,
+            generation_count,
+            evaluations,
+            fitness_history,
+        })
+    }
+}
+
+/// Mutation probability, that grows from `min` for the most diverse generation
+/// up to `max` for the generation of clones, to escape the premature convergence
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdaptiveMutation {
+    pub min: Probability,
+    pub max: Probability,
+}
+
+impl AdaptiveMutation {
+    /// Interpolates between the bounds by the [`diversity`](GenerationStats::diversity) in `0.0..=1.0`
+    pub fn probability(&self, diversity: f32) -> Probability {
+        let diversity = diversity.clamp(0.0, 1.0);
+        Probability(self.min.0 * diversity + self.max.0 * (1.0 - diversity))
+    }
+
+    /// Mutation probability for the non-empty `generation`
+    #[inline]
+    pub fn probability_for(&self, generation: &Generation) -> Probability {
+        self.probability(stats::diversity_of(generation))
+    }
+}
+
+/// Mutation probability, that decays with the generations from the `initial` one
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Schedule {
+    /// Changes evenly from `from` to `to` in the first `generations` and stays at `to` afterwards
+    Linear {
+        from: Probability,
+        to: Probability,
+        generations: NonZeroU32,
+    },
+    /// Multiplies the `initial` probability by the `rate` every generation
+    Exponential { initial: Probability, rate: f32 },
+}
+
+impl Schedule {
+    /// Probability after `generation` generations since the start of the search
+    pub fn value_at(&self, generation: u32) -> Probability {
+        match *self {
+            Schedule::Linear {
+                from,
+                to,
+                generations,
+            } => {
+                let t = generation.min(generations.get()) as f32 / generations.get() as f32;
+                Probability(from.0 * (1.0 - t) + to.0 * t)
+            }
+            Schedule::Exponential { initial, rate } => {
+                Probability((initial.0 * rate.powf(generation as f32)).clamp(0.0, 1.0))
+            }
+        }
+    }
+}
+
+/// Source of the mutation probability of every generation: constant, adaptive or scheduled
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct MutationControl {
+    constant: Probability,
+    adaptive: Option<AdaptiveMutation>,
+    schedule: Option<Schedule>,
+}
+
+impl MutationControl {
+    fn new(config: &Config) -> Self {
+        Self {
+            constant: config.mutation_probability,
+            adaptive: config.adaptive_mutation,
+            schedule: config.mutation_schedule,
+        }
+    }
+
+    /// Probability to mutate the offspring of the non-empty `generation` after `elapsed` generations
+    fn probability(&self, elapsed: u32, generation: &Generation) -> Probability {
+        match (self.adaptive, self.schedule) {
+            (Some(adaptive), _) => adaptive.probability_for(generation),
+            (None, Some(schedule)) => schedule.value_at(elapsed),
+            (None, None) => self.constant,
+        }
+    }
+}
+
+/// Detects, that the best fitness has stopped improving
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StagnationDetector {
+    window: NonZeroU32,
+    epsilon: f32,
+    best_fitness: Option<f32>,
+    generations_without_improvement: u32,
+}
+
+impl StagnationDetector {
+    /// The detector fires, once the best fitness has not improved by more than `epsilon` for `window` generations
+    pub fn new(window: NonZeroU32, epsilon: f32) -> Self {
+        Self {
+            window,
+            epsilon,
+            best_fitness: None,
+            generations_without_improvement: 0,
+        }
+    }
+
+    /// Registers the next generation and returns whether the search has stagnated
+    pub fn update(&mut self, generation: &Generation) -> bool {
+        let fitness = best_individual(generation).fitness();
+        match self.best_fitness {
+            Some(best) if fitness <= best + self.epsilon => {
+                self.generations_without_improvement += 1;
+            }
+            _ => {
+                self.best_fitness = Some(fitness);
+                self.generations_without_improvement = 0;
+            }
+        }
+        self.generations_without_improvement >= self.window.get()
+    }
+}
+
+/// Infinite iterator over the generations, starting with the initial one.
+/// Ends after the initial generation, if it is too small for the selection strategy.
+///
+/// Holds the random number generator, so the sequence is reproducible with the same seed
+#[derive(Debug, Clone)]
+pub struct Generations {
+    selection_strategy: SelectionStrategy,
+    mutation: MutationControl,
+    params: SelectionParams,
+    rng: SolverRng,
+    current: Generation,
+    started: bool,
+    /// Amount of the generations, produced after the initial one
+    elapsed: u32,
+}
+
+/// Lazily produces the generations described by the `config`.
+///
+/// The `config` is expected to be valid (see [`Config::validate`])
+pub fn generations(config: &Config) -> Generations {
+    let mut rng = config.rng();
+    let blocked = config.blocked_cells().ok().flatten().map(Arc::new);
+    let current = initial_generation(config, &mut rng)
+        .into_iter()
+        .map(|ch| ch.with_blocked(blocked.clone()))
+        .collect();
+    Generations {
+        selection_strategy: config.selection_strategy,
+        mutation: MutationControl::new(config),
+        params: config.selection_params(),
+        rng,
+        current,
+        started: false,
+        elapsed: 0,
+    }
+}
+
+impl Generations {
+    /// Pairs every generation with its statistics
+    pub fn with_stats(self) -> impl Iterator<Item = (Generation, GenerationStats)> {
+        (1..).zip(self).map(|(generation_count, generation)| {
+            let stats = stats::stats(generation_count, &generation);
+            (generation, stats)
+        })
+    }
+}
+
+impl Iterator for Generations {
+    type Item = Generation;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.started {
+            let mutation_probability = self.mutation.probability(self.elapsed, &self.current);
+            self.elapsed += 1;
+            self.current = selection::new_generation(
+                self.selection_strategy,
+                mem::take(&mut self.current),
+                mutation_probability,
+                self.params,
+                &mut self.rng,
+            )
+            .ok()?;
+        } else {
+            self.started = true;
+        }
+        Some(self.current.clone())
+    }
+}
+
+/// Random generation of the `config.piece`s around the fixed ones
+fn initial_generation(config: &Config, rng: &mut SolverRng) -> Generation {
+    let generation = Chromosome::create_rectangular_generation(
+        config.pieces(),
+        config.board_height(),
+        config.generation_size.get(),
+        rng,
+    )
+    .into_iter()
+    .map(|ch| ch.with_width(config.board_width()).with_piece(config.piece))
+    .collect();
+    pin_fixed(config, generation)
+}
+
+/// Moves the `config.fixed` pieces of every individual into their rows
+fn pin_fixed(config: &Config, generation: Generation) -> Generation {
+    let Some(fixed) = &config.fixed else {
+        return generation;
+    };
+    let fixed = Arc::new(fixed.clone());
+    generation
+        .into_par_iter()
+        .map(|ch| ch.with_fixed(Some(fixed.clone())))
+        .collect()
+}
+
+/// The fittest individual of the non-empty `generation`
+pub fn best_individual(generation: &Generation) -> &Chromosome {
+    generation
+        .par_iter()
+        .max_by(|ch1, ch2| ch1.fitness().total_cmp(&ch2.fitness()))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::RefCell,
+        num::NonZeroU32,
+        rc::Rc,
+        time::{Duration, Instant},
+    };
+
+    use clap::Parser;
+    use rand::Rng;
+
+    use crate::{
+        board::{BlockedCells, Board, Glyph, Piece},
+        chromosome::Chromosome,
+        config::Config,
+        selection::{Generation, Probability, Selection, SelectionCtx, SelectionParams},
+    };
+
+    use super::{
+        best_individual, collect_solutions, generations, solve, solve_with_stats, AdaptiveMutation,
+        Progress, Schedule, Solver, SolverState, StagnationDetector, Termination,
+    };
+
+    #[test]
+    fn test_solve() {
+        let config = Config::parse_from(["", "-b", "6", "--seed", "42"]);
+        let outcome = solve(&config).unwrap();
+        assert_eq!(Termination::Solved, outcome.termination);
+        assert!(!outcome.solutions.is_empty());
+        assert!(outcome.solutions.iter().all(|ch| ch.fitness() == 1.0));
+        assert_eq!(1.0, outcome.best.fitness());
+    }
+
+    #[test]
+    fn test_solve_8x8() {
+        let config = Config::parse_from(["", "-b", "8", "-s", "Tournament", "--seed", "42"]);
+        let outcome = solve(&config).unwrap();
+        assert_eq!(Termination::Solved, outcome.termination);
+        assert!(outcome.solutions.iter().any(|ch| ch.fitness() == 1.0));
+    }
+
+    #[test]
+    fn test_solutions_wanted() {
+        let config = Config::parse_from([
+            "",
+            "-b",
+            "8",
+            "-s",
+            "Tournament",
+            "--solutions-wanted",
+            "2",
+            "--seed",
+            "42",
+        ]);
+        let outcome = solve(&config).unwrap();
+        assert_eq!(Termination::Solved, outcome.termination);
+        assert!(outcome.solutions.len() >= 2);
+        assert!(outcome.solutions.iter().all(|ch| ch.fitness() == 1.0));
+    }
+
+    #[test]
+    fn test_collect_all() {
+        let config = Config::parse_from([
+            "",
+            "-b",
+            "6",
+            "-g",
+            "30",
+            "-s",
+            "Tournament",
+            "--collect-all",
+            "--max-generations",
+            "300",
+            "--immigrant-rate",
+            "0.2",
+            "--seed",
+            "42",
+        ]);
+        let outcome = solve(&config).unwrap();
+        assert_eq!(Termination::ExhaustedGenerations, outcome.termination);
+        assert_eq!(300, outcome.generation_count);
+        assert!(outcome.solutions.len() > 1);
+        assert!(outcome.solutions.len() <= 4);
+        assert!(outcome.solutions.iter().all(|ch| ch.fitness() == 1.0));
+        assert_eq!(outcome.solutions, collect_solutions(&config).unwrap());
+    }
+
+    #[test]
+    fn test_solve_rectangular() {
+        for (width, height, piece) in [("6", "9", "queen"), ("8", "4", "knight")] {
+            let config = Config::parse_from([
+                "",
+                "--board-width",
+                width,
+                "--board-height",
+                height,
+                "-s",
+                "Tournament",
+                "--piece",
+                piece,
+                "--seed",
+                "42",
+            ]);
+            assert!(config.validate().is_ok());
+            let outcome = solve(&config).unwrap();
+            assert_eq!(Termination::Solved, outcome.termination, "{width}x{height}");
+            for solution in &outcome.solutions {
+                assert_eq!(config.board_width(), solution.width());
+                assert_eq!(config.board_height(), solution.height());
+                assert_eq!(1.0, solution.fitness());
+            }
+        }
+    }
+
+    #[test]
+    fn test_solve_fewer_queens() {
+        let config = Config::parse_from(["", "-b", "10", "--queens", "4", "--seed", "42"]);
+        let outcome = solve(&config).unwrap();
+        assert_eq!(Termination::Solved, outcome.termination);
+        let solution = outcome.solutions.iter().next().unwrap();
+        assert_eq!((10, 10), (solution.width(), solution.height()));
+        assert_eq!(4, solution.genes().len());
+        assert_eq!(0, Board::from(solution).beats_count());
+
+        // the empty columns are rendered too
+        let rendered = Board::from(solution).render_plain();
+        assert_eq!(10, rendered.lines().count());
+        assert!(rendered.lines().all(|row| row.len() == 10));
+        assert_eq!(4, rendered.matches('Q').count());
+        assert!(rendered.lines().all(|row| row[4..] == *".".repeat(6)));
+        let fen = Board::from(solution).to_fen();
+        assert_eq!(10, fen.split('/').count());
+        // the pieces are in the leftmost 4 columns
+        assert!(fen
+            .split('/')
+            .all(|row| row == "10" || row.ends_with(['6', '7', '8', '9'])));
+    }
+
+    #[test]
+    fn test_solve_avoids_blocked_cells() {
+        let config = Config::parse_from(["", "-b", "8", "--seed", "42"]);
+        let blocked: BlockedCells = (0..4).map(|x| (x, 0)).chain([(5, 5), (7, 2)]).collect();
+        let outcome = Solver::new(&config)
+            .with_blocked_cells(blocked.clone())
+            .solve()
+            .unwrap();
+        assert_eq!(Termination::Solved, outcome.termination);
+        let solution = outcome.solutions.iter().next().unwrap();
+        assert_eq!(Some(&blocked), solution.blocked());
+        assert_eq!(0, Board::from(solution).beats_count());
+        assert!(solution
+            .genes()
+            .iter()
+            .enumerate()
+            .all(|(x, y)| !blocked.contains(x as u16, *y)));
+    }
+
+    #[test]
+    fn test_solve_with_blocked_file() {
+        let path = std::env::temp_dir().join(format!(
+            "queen-placement-solver-blocked-{}.txt",
+            std::process::id()
+        ));
+        std::fs::write(&path, "0 0\n1 0\n2 0\n3 0\n5 5\n7 2\n").unwrap();
+        let config = Config::parse_from([
+            "",
+            "-b",
+            "8",
+            "--blocked-file",
+            path.to_str().unwrap(),
+            "--seed",
+            "42",
+        ]);
+        let blocked = config.blocked_cells().unwrap().unwrap();
+        let outcome = solve(&config).unwrap();
+        let first = generations(&config).next().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(Termination::Solved, outcome.termination);
+        for solution in &outcome.solutions {
+            assert_eq!(Some(&blocked), solution.blocked());
+            assert_eq!(0, Board::from(solution).blocked_count());
+        }
+        assert!(first.iter().all(|ch| ch.blocked() == Some(&blocked)));
+    }
+
+    #[test]
+    fn test_solve_around_fixed_pieces() {
+        let config = Config::parse_from(["", "-b", "8", "--fixed", "0:3,5:2", "--seed", "42"]);
+        let outcome = solve(&config).unwrap();
+        assert_eq!(Termination::Solved, outcome.termination);
+        for solution in &outcome.solutions {
+            assert_eq!((3, 2), (solution.genes()[0], solution.genes()[5]));
+            assert_eq!(0, Board::from(solution).beats_count());
+        }
+    }
+
+    #[test]
+    fn test_solve_bishops() {
+        let config = Config::parse_from([
+            "",
+            "-b",
+            "8",
+            "-s",
+            "Tournament",
+            "--piece",
+            "bishop",
+            "--seed",
+            "42",
+        ]);
+        let outcome = solve(&config).unwrap();
+        assert_eq!(Termination::Solved, outcome.termination);
+        assert!(outcome
+            .solutions
+            .iter()
+            .all(|ch| ch.piece() == Piece::Bishop && ch.fitness() == 1.0));
+    }
+
+    #[test]
+    fn test_unique_up_to_symmetry() {
+        let config = Config::parse_from([
+            "",
+            "-b",
+            "6",
+            "-g",
+            "30",
+            "-s",
+            "Tournament",
+            "--solutions-wanted",
+            "2",
+            "--unique-up-to-symmetry",
+            "--max-generations",
+            "500",
+            "--seed",
+            "42",
+        ]);
+        let outcome = solve(&config).unwrap();
+        // the 4 solutions of the 6x6 board are all symmetric to each other
+        assert_eq!(Termination::ExhaustedGenerations, outcome.termination);
+        assert_eq!(1, outcome.solutions.len());
+    }
+
+    #[test]
+    fn test_unique_up_to_symmetry_avoids_blocked_cells() {
+        let config = Config::parse_from([
+            "",
+            "-b",
+            "6",
+            "--unique-up-to-symmetry",
+            "--solutions-wanted",
+            "2",
+            "--max-generations",
+            "300",
+            "--seed",
+            "42",
+        ]);
+        // the canonical form [1, 3, 5, 0, 2, 4] of all the 4 solutions of the 6x6 board stands on both cells
+        let blocked: BlockedCells = [(0, 1), (1, 0)].into_iter().collect();
+        let outcome = Solver::new(&config)
+            .with_blocked_cells(blocked.clone())
+            .solve()
+            .unwrap();
+        assert!(!outcome.solutions.is_empty());
+        for solution in &outcome.solutions {
+            assert_eq!(0, Board::from(solution).blocked_count(), "{solution:?}");
+            assert!(Board::from(solution).is_solution());
+        }
+    }
+
+    #[test]
+    fn test_unique_up_to_symmetry_keeps_fixed_pieces() {
+        let config = Config::parse_from([
+            "",
+            "-b",
+            "6",
+            "--fixed",
+            "0:3",
+            "--unique-up-to-symmetry",
+            "--seed",
+            "1",
+        ]);
+        let outcome = solve(&config).unwrap();
+        assert_eq!(Termination::Solved, outcome.termination);
+        for solution in &outcome.solutions {
+            assert_eq!(3, solution.genes()[0], "{solution:?}");
+            assert!(Board::from(solution).is_solution());
+        }
+    }
+
+    #[test]
+    fn test_solve_max_generations() {
+        let config = Config::parse_from(["", "-b", "3", "--max-generations", "5"]);
+        let outcome = solve(&config).unwrap();
+        assert_eq!(Termination::ExhaustedGenerations, outcome.termination);
+        assert!(outcome.solutions.is_empty());
+        assert_eq!(5, outcome.generation_count);
+    }
+
+    #[test]
+    fn test_unsolvable_terminates() {
+        for board_size in ["2", "3"] {
+            let config = Config::parse_from([
+                "",
+                "-b",
+                board_size,
+                "-g",
+                "10",
+                "--max-generations",
+                "50",
+                "--seed",
+                "42",
+            ]);
+            let outcome = solve(&config).unwrap();
+            assert_eq!(Termination::ExhaustedGenerations, outcome.termination);
+            assert_eq!(50, outcome.generation_count);
+            assert!(outcome.best.fitness() < 1.0);
+        }
+    }
+
+    #[test]
+    fn test_solve_time_limit() {
+        let config = Config::parse_from(["", "-b", "3", "--time-limit", "1ms"]);
+        let start = Instant::now();
+        let outcome = solve(&config).unwrap();
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert_eq!(Termination::TimeLimit, outcome.termination);
+        assert!(outcome.best.fitness() < 1.0);
+    }
+
+    #[test]
+    fn test_generations_start_with_initial() {
+        let config = Config::parse_from(["", "-b", "8", "-g", "20", "--seed", "42"]);
+        let initial = Chromosome::create_generation(8, 20, &mut config.rng());
+        let mut generations = generations(&config);
+        assert_eq!(Some(initial), generations.next());
+        assert!(generations.take(5).all(|generation| generation.len() == 20));
+    }
+
+    #[test]
+    fn test_generations_keep_fixed_pieces() {
+        let config = Config::parse_from([
+            "", "-b", "8", "--fixed", "0:3,5:2", "-p", "1.0", "--seed", "42",
+        ]);
+        for generation in generations(&config).take(10) {
+            for chromosome in &generation {
+                assert_eq!((3, 2), (chromosome.genes()[0], chromosome.genes()[5]));
+                assert!(chromosome.is_permutation());
+            }
+        }
+    }
+
+    #[test]
+    fn test_generations_are_deterministic() {
+        let config = Config::parse_from(["", "-b", "8", "-g", "20", "--seed", "42"]);
+        let first: Vec<_> = generations(&config).take(5).collect();
+        let second: Vec<_> = generations(&config).take(5).collect();
+        assert_eq!(5, first.len());
+        assert!(first.iter().all(|generation| generation.len() == 20));
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_generations_with_stats() {
+        let config = Config::parse_from(["", "-b", "8", "-g", "20", "--seed", "42"]);
+        let with_stats: Vec<_> = generations(&config).with_stats().take(5).collect();
+        for (i, (generation, stats)) in with_stats.iter().enumerate() {
+            assert_eq!(i as u32 + 1, stats.generation);
+            assert_eq!(best_individual(generation).fitness(), stats.best);
+            assert!(stats.worst <= stats.median && stats.median <= stats.best);
+        }
+        let generations: Vec<_> = generations(&config).take(5).collect();
+        assert!(with_stats
+            .into_iter()
+            .map(|(generation, _)| generation)
+            .eq(generations));
+    }
+
+    #[test]
+    fn test_rendering_does_not_affect_solve() {
+        let config = Config::parse_from(["", "-b", "8", "--seed", "42", "--quiet"]);
+        let headless = solve(&config).unwrap();
+        let mut rendered = 0;
+        let drawn = Solver::new(&config)
+            .solve_with_progress(|progress| {
+                if config.shows_progress(progress.generation_count) {
+                    unreachable!("The quiet search draws nothing");
+                }
+                let board = Board::from(best_individual(progress.generation));
+                rendered += board.with_glyph(Glyph::Blocks).to_string().len();
+            })
+            .unwrap();
+        assert!(rendered > 0);
+        assert_eq!(headless.termination, drawn.termination);
+        assert_eq!(headless.solutions, drawn.solutions);
+        assert_eq!(headless.best, drawn.best);
+        assert_eq!(headless.generation_count, drawn.generation_count);
+        assert_eq!(headless.evaluations, drawn.evaluations);
+    }
+
+    #[test]
+    fn test_solve_with_stats() {
+        let config = Config::parse_from(["", "-b", "8", "--seed", "42", "--max-generations", "30"]);
+        let mut history = Vec::new();
+        let outcome = solve_with_stats(&config, |stats| history.push(*stats)).unwrap();
+        assert_eq!(outcome.generation_count as usize, history.len());
+        assert!(history
+            .iter()
+            .zip(1..)
+            .all(|(stats, generation)| stats.generation == generation));
+        assert_eq!(outcome.best.fitness(), history.last().unwrap().best);
+    }
+
+    #[test]
+    fn test_fitness_history() {
+        let config = Config::parse_from([
+            "",
+            "-b",
+            "12",
+            "-s",
+            "Tournament",
+            "--elitism",
+            "1",
+            "--seed",
+            "42",
+            "--max-generations",
+            "50",
+        ]);
+        assert!(solve(&config).unwrap().fitness_history.is_empty());
+
+        let outcome = Solver::new(&config).with_fitness_history().solve().unwrap();
+        let history = &outcome.fitness_history;
+        assert_eq!(outcome.generation_count as usize, history.len());
+        assert!(
+            history.windows(2).all(|pair| pair[0] <= pair[1]),
+            "{history:?}"
+        );
+        assert_eq!(outcome.best.fitness(), *history.last().unwrap());
+    }
+
+    #[test]
+    fn test_same_seed_same_first_generation() {
+        let first_generation = |config: &Config| {
+            let mut first = None;
+            Solver::new(config)
+                .solve_with_progress(|progress: Progress<'_>| {
+                    first.get_or_insert_with(|| progress.generation.clone());
+                })
+                .unwrap();
+            first.unwrap()
+        };
+        let config = Config::parse_from(["", "-b", "8", "--max-generations", "3", "--seed", "7"]);
+        assert_eq!(first_generation(&config), first_generation(&config));
+        let other = Config::parse_from(["", "-b", "8", "--max-generations", "3", "--seed", "8"]);
+        assert_ne!(first_generation(&config), first_generation(&other));
+    }
+
+    #[test]
+    fn test_deterministic_solve() {
+        let config = Config::parse_from([
+            "",
+            "-b",
+            "8",
+            "-s",
+            "Tournament",
+            "--solutions-wanted",
+            "3",
+            "--seed",
+            "42",
+            "--deterministic",
+        ]);
+        let solve_on = |threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap()
+                .install(|| solve(&config).unwrap())
+        };
+        let first = solve_on(1);
+        let second = solve_on(1);
+        assert_eq!(Termination::Solved, first.termination);
+        assert_eq!(first.solutions, second.solutions);
+        assert_eq!(first.generation_count, second.generation_count);
+        // the forked generators make the parallel search reproducible as well
+        assert_eq!(first.solutions, solve_on(4).solutions);
+    }
+
+    /// Keeps the generation as is, recording the mutation probabilities
+    struct RecordMutationProbability(Rc<RefCell<Vec<Probability>>>);
+
+    impl Selection for RecordMutationProbability {
+        fn next_generation(
+            &mut self,
+            generation: Generation,
+            ctx: &mut SelectionCtx<'_>,
+        ) -> Generation {
+            self.0.borrow_mut().push(ctx.mutation_probability);
+            generation
+        }
+    }
+
+    #[test]
+    fn test_adaptive_mutation() {
+        let adaptive: AdaptiveMutation = "0.1..0.9".parse().unwrap();
+        assert_eq!(Probability(0.9), adaptive.probability(0.0));
+        assert_eq!(Probability(0.1), adaptive.probability(1.0));
+        assert!(adaptive.probability(0.25) > adaptive.probability(0.5));
+        assert!("0.9..0.1".parse::<AdaptiveMutation>().is_err());
+        assert!("0.1-0.9".parse::<AdaptiveMutation>().is_err());
+        assert!("0.1..1.5".parse::<AdaptiveMutation>().is_err());
+
+        let config = Config::parse_from([
+            "",
+            "--adaptive-mutation",
+            "0.1..0.9",
+            "--max-generations",
+            "3",
+            "--seed",
+            "42",
+        ]);
+        let run = |generation: Generation| {
+            let recorded = Rc::new(RefCell::new(Vec::new()));
+            let state = SolverState {
+                generation,
+                generation_count: 1,
+                evaluations: 0,
+                rng: config.rng(),
+            };
+            Solver::new(&config)
+                .with_selection(Box::new(RecordMutationProbability(recorded.clone())))
+                .resume_from(state)
+                .solve()
+                .unwrap();
+            recorded.take()
+        };
+        // the uniform generation is mutated with the highest probability
+        let clones = vec![Chromosome::from(vec![0, 1, 2, 3, 4, 5, 6, 7]); 20];
+        assert_eq!(vec![Probability(0.9); 2], run(clones));
+        let random = Chromosome::create_generation(8, 20, &mut config.rng());
+        assert!(run(random).iter().all(|p| p.0 < 0.3));
+    }
+
+    #[test]
+    fn test_schedule() {
+        let linear: Schedule = "linear:0.3..0.05@100".parse().unwrap();
+        assert_eq!(Probability(0.3), linear.value_at(0));
+        assert!((linear.value_at(50).0 - 0.175).abs() < 1e-6);
+        // clamped at the end of the schedule
+        for generation in [100, 101, u32::MAX] {
+            assert_eq!(Probability(0.05), linear.value_at(generation));
+        }
+
+        let exponential: Schedule = "exp:0.3,0.9".parse().unwrap();
+        assert_eq!(Probability(0.3), exponential.value_at(0));
+        assert!((exponential.value_at(2).0 - 0.243).abs() < 1e-6);
+        assert_eq!(Probability(0.0), exponential.value_at(u32::MAX));
+
+        for schedule in [linear, exponential] {
+            let values: Vec<f32> = (0..200).map(|g| schedule.value_at(g).0).collect();
+            assert!(values.windows(2).all(|w| w[1] <= w[0]), "{schedule:?}");
+        }
+    }
+
+    #[test]
+    fn test_scheduled_mutation() {
+        let config = Config::parse_from([
+            "",
+            "--mutation-schedule",
+            "linear:0.4..0.1@3",
+            "--max-generations",
+            "6",
+            "--seed",
+            "42",
+        ]);
+        let recorded = Rc::new(RefCell::new(Vec::new()));
+        Solver::new(&config)
+            .with_selection(Box::new(RecordMutationProbability(recorded.clone())))
+            .solve()
+            .unwrap();
+        let recorded = recorded.take();
+        // one probability per generation after the initial one
+        assert_eq!(5, recorded.len());
+        for (probability, expected) in recorded.iter().zip([0.4, 0.3, 0.2, 0.1, 0.1]) {
+            assert!((probability.0 - expected).abs() < 1e-6, "{recorded:?}");
+        }
+    }
+
+    /// Keeps everything, but swaps two genes of one random individual
+    struct MutateOne;
+
+    impl Selection for MutateOne {
+        fn next_generation(
+            &mut self,
+            mut generation: Generation,
+            ctx: &mut SelectionCtx<'_>,
+        ) -> Generation {
+            let i = ctx.rng.gen_range(0..generation.len());
+            let genes = generation[i].genes_mut();
+            let (a, b) = (
+                ctx.rng.gen_range(0..genes.len()),
+                ctx.rng.gen_range(0..genes.len()),
+            );
+            genes.swap(a, b);
+            generation
+        }
+
+        fn evaluations_per_generation(
+            &self,
+            _population_size: usize,
+            _params: SelectionParams,
+        ) -> usize {
+            1
+        }
+    }
+
+    #[test]
+    fn test_custom_selection() {
+        let config = Config::parse_from(["", "-b", "3", "-g", "10", "--max-generations", "20"]);
+        let outcome = Solver::new(&config)
+            .with_selection(Box::new(MutateOne))
+            .solve()
+            .unwrap();
+        assert_eq!(Termination::ExhaustedGenerations, outcome.termination);
+        assert_eq!(20, outcome.generation_count);
+        assert_eq!(10 + 19, outcome.evaluations);
+
+        let config = Config::parse_from(["", "-b", "5", "-g", "10", "--seed", "42"]);
+        let outcome = Solver::new(&config)
+            .with_selection(Box::new(MutateOne))
+            .solve()
+            .unwrap();
+        assert_eq!(Termination::Solved, outcome.termination);
+    }
+
+    #[test]
+    fn test_stagnation_detector() {
+        let generation = vec![Chromosome::from(vec![0, 1, 2, 3])];
+        let mut detector = StagnationDetector::new(NonZeroU32::new(5).unwrap(), 0.0);
+        assert!(!detector.update(&generation));
+        for _ in 1..5 {
+            assert!(!detector.update(&generation));
+        }
+        assert!(detector.update(&generation));
+
+        let mut detector = StagnationDetector::new(NonZeroU32::new(2).unwrap(), 0.01);
+        assert!(!detector.update(&vec![Chromosome::from(vec![0, 1, 2, 3])]));
+        assert!(!detector.update(&vec![Chromosome::from(vec![1, 3, 0, 2])]));
+        assert!(!detector.update(&vec![Chromosome::from(vec![1, 3, 0, 2])]));
+        assert!(detector.update(&vec![Chromosome::from(vec![1, 3, 0, 2])]));
+    }
+
+    #[test]
+    fn test_solve_stagnation() {
+        let config = Config::parse_from(["", "-b", "3", "--stagnation-generations", "10"]);
+        let outcome = solve(&config).unwrap();
+        assert_eq!(Termination::Stagnation, outcome.termination);
+    }
+}
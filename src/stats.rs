@@ -0,0 +1,248 @@
+use std::{collections::HashSet, fmt};
+
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::{chromosome::Chromosome, selection::Generation};
+
+/// Summary of the fitness of one generation, e.g. for plotting the convergence
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GenerationStats {
+    /// Number of the generation, starting with 1 for the initial one
+    pub generation: u32,
+    pub best: f32,
+    pub worst: f32,
+    pub mean: f32,
+    pub median: f32,
+    /// Mean Hamming distance between two individuals relative to the amount of genes:
+    /// `0.0` for the clones, `1.0` if no two individuals share a gene at the same position
+    pub diversity: f32,
+    /// Amount of the distinct individuals
+    pub unique_count: usize,
+}
+
+/// Names of the columns of [`GenerationStats::to_csv_row`]
+pub const CSV_HEADER: &str = "generation,best,worst,mean,median,diversity";
+
+impl GenerationStats {
+    /// Comma-separated values of the columns of the [`CSV_HEADER`]
+    pub fn to_csv_row(&self) -> String {
+        let Self {
+            generation,
+            best,
+            worst,
+            mean,
+            median,
+            diversity,
+            unique_count: _,
+        } = self;
+        format!("{generation},{best},{worst},{mean},{median},{diversity}")
+    }
+}
+
+impl fmt::Display for GenerationStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Generation {}: best {}, worst {}, mean {}, median {}, diversity {}, {} unique",
+            self.generation,
+            self.best,
+            self.worst,
+            self.mean,
+            self.median,
+            self.diversity,
+            self.unique_count
+        )
+    }
+}
+
+/// Values, accumulated in one pass over the generation
+#[derive(Default)]
+struct Totals<'g> {
+    fitness: Vec<f32>,
+    /// Amount of the individuals with the gene at the position, indexed by `position * height + gene`
+    gene_counts: Vec<usize>,
+    unique: HashSet<&'g Chromosome>,
+}
+
+impl<'g> Totals<'g> {
+    fn merge(mut self, other: Self) -> Self {
+        self.fitness.extend(other.fitness);
+        if self.gene_counts.is_empty() {
+            self.gene_counts = other.gene_counts;
+        } else if !other.gene_counts.is_empty() {
+            self.gene_counts
+                .iter_mut()
+                .zip(other.gene_counts)
+                .for_each(|(total, count)| *total += count);
+        }
+        self.unique.extend(other.unique);
+        self
+    }
+}
+
+/// Computes the statistics of the non-empty `generation` number `generation_count` in one parallel pass
+pub fn stats(generation_count: u32, generation: &Generation) -> GenerationStats {
+    let (width, height) = (generation[0].genes().len(), generation[0].height() as usize);
+    let Totals {
+        mut fitness,
+        gene_counts,
+        unique,
+    } = generation
+        .par_iter()
+        .fold(Totals::default, |mut totals, ch| {
+            totals.fitness.push(ch.fitness());
+            if totals.gene_counts.is_empty() {
+                totals.gene_counts = vec![0; width * height];
+            }
+            for (position, gene) in ch.genes().iter().enumerate() {
+                totals.gene_counts[position * height + *gene as usize] += 1;
+            }
+            totals.unique.insert(ch);
+            totals
+        })
+        .reduce(Totals::default, Totals::merge);
+    // the same order as in the `Ord` of the `Board`
+    fitness.sort_unstable_by(f32::total_cmp);
+
+    let n = fitness.len();
+    let median = if n % 2 == 1 {
+        fitness[n / 2]
+    } else {
+        (fitness[n / 2 - 1] + fitness[n / 2]) / 2.0
+    };
+
+    GenerationStats {
+        generation: generation_count,
+        best: fitness[n - 1],
+        worst: fitness[0],
+        mean: fitness.iter().sum::<f32>() / n as f32,
+        median,
+        diversity: diversity(&gene_counts, n, width),
+        unique_count: unique.len(),
+    }
+}
+
+/// [`GenerationStats::diversity`] of the non-empty `generation`, without the rest of the statistics
+pub fn diversity_of(generation: &Generation) -> f32 {
+    let (width, height) = (generation[0].genes().len(), generation[0].height() as usize);
+    let gene_counts = generation
+        .par_iter()
+        .fold(
+            || vec![0; width * height],
+            |mut counts, ch| {
+                for (position, gene) in ch.genes().iter().enumerate() {
+                    counts[position * height + *gene as usize] += 1;
+                }
+                counts
+            },
+        )
+        .reduce_with(|mut total, counts| {
+            total.iter_mut().zip(counts).for_each(|(t, c)| *t += c);
+            total
+        })
+        .unwrap_or_default();
+    diversity(&gene_counts, generation.len(), width)
+}
+
+/// Mean pairwise Hamming distance of `n` individuals with `width` genes, relative to the `width`.
+///
+/// `c` individuals with the same gene at a position make `c * (c - 1)` of the `n * (n - 1)` ordered pairs,
+/// that agree there
+fn diversity(gene_counts: &[usize], n: usize, width: usize) -> f32 {
+    if n < 2 || width == 0 {
+        return 0.0;
+    }
+    let pairs = n * (n - 1);
+    let agreeing: usize = gene_counts.iter().map(|c| c * c.saturating_sub(1)).sum();
+    (pairs * width - agreeing) as f32 / (pairs * width) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chromosome::Chromosome;
+
+    use crate::solver::best_individual;
+
+    use super::{diversity_of, stats, GenerationStats, CSV_HEADER};
+
+    #[test]
+    fn test_generation_stats() {
+        // 0, 0, 3 and 3 beaten queens
+        let generation = vec![
+            Chromosome::from(vec![0, 2, 4, 1, 3]),
+            Chromosome::from(vec![0, 2, 4, 1, 3]),
+            Chromosome::from(vec![0, 4, 1, 3, 2]),
+            Chromosome::from(vec![0, 2, 4, 3, 1]),
+        ];
+
+        assert_eq!(
+            GenerationStats {
+                generation: 7,
+                best: 1.0,
+                worst: 0.25,
+                mean: 0.625,
+                median: 0.625,
+                // Hamming distances 0, 4, 2, 4, 2 and 3 between 5 genes
+                diversity: 0.5,
+                unique_count: 3,
+            },
+            stats(7, &generation)
+        );
+    }
+
+    #[test]
+    fn test_median_of_odd_generation() {
+        let generation = vec![
+            Chromosome::from(vec![0, 2, 4, 1, 3]),
+            Chromosome::from(vec![4, 0, 1, 3, 2]),
+            Chromosome::from(vec![0, 4, 1, 3, 2]),
+        ];
+        let stats = stats(1, &generation);
+        assert_eq!(0.25, stats.median);
+        assert_eq!(0.2, stats.worst);
+    }
+
+    #[test]
+    fn test_diversity_is_mean_hamming_distance() {
+        let generation = Chromosome::create_generation(8, 30, &mut rand::thread_rng());
+        let mut total = 0;
+        for ch1 in &generation {
+            for ch2 in &generation {
+                total += ch1.hamming_distance(ch2);
+            }
+        }
+        let expected = total as f32 / (30 * 29 * 8) as f32;
+        assert!((expected - stats(1, &generation).diversity).abs() < 1e-6);
+        assert_eq!(stats(1, &generation).diversity, diversity_of(&generation));
+
+        let clones = vec![generation[0].clone(); 10];
+        assert_eq!(0.0, stats(1, &clones).diversity);
+        assert_eq!(1, stats(1, &clones).unique_count);
+    }
+
+    #[test]
+    fn test_csv_row() {
+        let generation = vec![
+            Chromosome::from(vec![0, 2, 4, 1, 3]),
+            Chromosome::from(vec![0, 2, 4, 1, 3]),
+            Chromosome::from(vec![0, 4, 1, 3, 2]),
+            Chromosome::from(vec![0, 2, 4, 3, 1]),
+        ];
+        assert_eq!(
+            "Generation 7: best 1, worst 0.25, mean 0.625, median 0.625, diversity 0.5, 3 unique",
+            stats(7, &generation).to_string()
+        );
+        let row = stats(7, &generation).to_csv_row();
+        assert_eq!("7,1,0.25,0.625,0.625,0.5", row);
+        assert_eq!(CSV_HEADER.split(',').count(), row.split(',').count());
+
+        // the best column is the fitness, that is shown during the search
+        let generation = Chromosome::create_generation(8, 30, &mut rand::thread_rng());
+        let row = stats(1, &generation).to_csv_row();
+        assert_eq!(
+            best_individual(&generation).fitness().to_string(),
+            row.split(',').nth(1).unwrap()
+        );
+    }
+}